@@ -0,0 +1,524 @@
+//! Creating a [`Bot`] with more customization than [`Bot::new`]
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+#[cfg(feature = "cache")]
+use twilight_cache_inmemory::InMemoryCacheBuilder;
+use twilight_gateway::{
+    Config,
+    ConfigBuilder,
+    EventTypeFlags,
+    Intents,
+    Session,
+    Shard,
+    ShardId,
+    stream,
+};
+use twilight_http::{Client, client::ClientBuilder};
+use twilight_model::{
+    channel::message::AllowedMentions,
+    gateway::payload::outgoing::update_presence::UpdatePresencePayload,
+    id::{Id, marker::UserMarker},
+};
+
+use crate::{
+    Bot,
+    Shards,
+    error::Error,
+    log::{LogCache, LogIdentity, LogQueue},
+    session::SessionStore,
+    shutdown::Tasks,
+    status::ShardTracker,
+};
+
+/// Builder to create a [`Bot`] and its [`Shards`]
+///
+/// By default, the shards recommended by Discord are started and the bot's
+/// application and user info are fetched
+///
+/// # Compression
+///
+/// Gateway compression is enabled at compile time with the `zlib-stock` or
+/// `zlib-simd` Cargo features
+#[derive(Debug)]
+#[must_use]
+pub struct BotBuilder {
+    #[cfg(feature = "cache")]
+    cache: InMemoryCacheBuilder,
+    fetch_application: bool,
+    fetch_user: bool,
+    gateway: ConfigBuilder,
+    http: ClientBuilder,
+    large_threshold: Option<u64>,
+    session_store: Option<Box<dyn SessionStore>>,
+    shards: ShardSelection,
+}
+
+impl BotBuilder {
+    /// Create the bot and its shards
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidLargeThreshold`] if the
+    /// [`BotBuilder::large_threshold`] isn't between 50 and 250
+    ///
+    /// Returns [`Error::InvalidShardSelection`] if the
+    /// [`ShardSelection`] is invalid
+    ///
+    /// Returns [`Error::StartRecommended`] if getting the recommended shards
+    /// fails
+    ///
+    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if getting the
+    /// application or user info fails
+    ///
+    /// Returns [`Error::InvalidToken`] if [`BotBuilder::fetch_user`] is
+    /// disabled and the bot's user ID can't be read from the token
+    ///
+    /// Returns [`Error::Io`] if loading the sessions from the
+    /// [`BotBuilder::session_store`] fails
+    pub async fn build(self) -> Result<(Bot, Shards), Error> {
+        let mut gateway = self.gateway;
+        if let Some(large_threshold) = self.large_threshold {
+            if !(50..=250).contains(&large_threshold) {
+                return Err(Error::InvalidLargeThreshold(large_threshold));
+            }
+            gateway = gateway.large_threshold(large_threshold);
+        }
+
+        let http = self.http.build();
+        let config = gateway.build();
+
+        let sessions = match &self.session_store {
+            Some(store) => store.load()?,
+            None => HashMap::new(),
+        };
+        let shards = self.shards.create(&http, config, &sessions).await?;
+
+        let application = if self.fetch_application {
+            Some(http.current_user_application().await?.model().await?)
+        } else {
+            None
+        };
+        let user = if self.fetch_user {
+            Some(http.current_user().await?.model().await?)
+        } else {
+            None
+        };
+
+        let user_id = match &user {
+            Some(current_user) => current_user.id,
+            None => http
+                .token()
+                .and_then(token_user_id)
+                .ok_or(Error::InvalidToken)?,
+        };
+        let application_id = application.as_ref().map_or_else(
+            || user_id.cast(),
+            |current_application| current_application.id,
+        );
+
+        Ok((
+            Bot {
+                application,
+                application_id,
+                #[cfg(feature = "cache")]
+                cache: Arc::new(self.cache.build()),
+                http: Arc::new(http),
+                log_cache: LogCache::default(),
+                log_fallbacks: vec![],
+                log_identity: LogIdentity::default(),
+                log_queue: LogQueue::default(),
+                log_redactions: vec![],
+                log_targets: vec![],
+                logging_webhook: None,
+                shard_selection: self.shards,
+                shard_tracker: ShardTracker::default(),
+                tasks: Tasks::default(),
+                user,
+                user_id,
+            },
+            Shards(shards),
+        ))
+    }
+
+    /// Customize the in-memory cache
+    ///
+    /// For example, to only cache some resource types
+    #[cfg(feature = "cache")]
+    pub fn cache<F: FnOnce(InMemoryCacheBuilder) -> InMemoryCacheBuilder>(
+        mut self,
+        configure: F,
+    ) -> Self {
+        self.cache = configure(self.cache);
+        self
+    }
+
+    /// Set the default allowed mentions of the HTTP client
+    ///
+    /// See [`Reply::allowed_mentions`] to override this per reply
+    ///
+    /// [`Reply::allowed_mentions`]: crate::reply::Reply::allowed_mentions
+    pub fn default_allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.http = self.http.default_allowed_mentions(allowed_mentions);
+        self
+    }
+
+    /// Set the event types the shards receive
+    ///
+    /// Defaults to all event types
+    pub fn event_types(mut self, event_types: EventTypeFlags) -> Self {
+        self.gateway = self.gateway.event_types(event_types);
+        self
+    }
+
+    /// Set whether to fetch the bot's application info, setting
+    /// [`Bot::application`]
+    ///
+    /// When disabled, [`Bot::application_id`] is assumed to be the bot's user
+    /// ID
+    ///
+    /// Defaults to `true`
+    pub const fn fetch_application(mut self, fetch_application: bool) -> Self {
+        self.fetch_application = fetch_application;
+        self
+    }
+
+    /// Set whether to fetch the bot's user info, setting [`Bot::user`]
+    ///
+    /// When disabled, [`Bot::user_id`] is read from the token
+    ///
+    /// Defaults to `true`
+    pub const fn fetch_user(mut self, fetch_user: bool) -> Self {
+        self.fetch_user = fetch_user;
+        self
+    }
+
+    /// Customize the gateway configuration further
+    ///
+    /// Use this for options that don't have a method in this builder
+    pub fn gateway_config<F: FnOnce(ConfigBuilder) -> ConfigBuilder>(
+        mut self,
+        configure: F,
+    ) -> Self {
+        self.gateway = configure(self.gateway);
+        self
+    }
+
+    /// Customize the HTTP client further
+    ///
+    /// Use this for options that don't have a method in this builder
+    pub fn http_client<F: FnOnce(ClientBuilder) -> ClientBuilder>(mut self, configure: F) -> Self {
+        self.http = configure(self.http);
+        self
+    }
+
+    /// Set the threshold of members after which a guild is considered large
+    /// and offline members aren't sent in its guild create event
+    ///
+    /// Defaults to 50, [`BotBuilder::build`] returns
+    /// [`Error::InvalidLargeThreshold`] if it isn't between 50 and 250
+    pub const fn large_threshold(mut self, large_threshold: u64) -> Self {
+        self.large_threshold = Some(large_threshold);
+        self
+    }
+
+    /// Create a new builder with the given token and intents
+    pub fn new<T: Into<String>>(token: T, intents: Intents) -> Self {
+        let token_string = token.into();
+
+        Self {
+            #[cfg(feature = "cache")]
+            cache: InMemoryCacheBuilder::new(),
+            fetch_application: true,
+            fetch_user: true,
+            gateway: ConfigBuilder::new(token_string.clone(), intents),
+            http: ClientBuilder::new().token(token_string),
+            large_threshold: None,
+            session_store: None,
+            shards: ShardSelection::Recommended,
+        }
+    }
+
+    /// Set the presence the shards identify with
+    pub fn presence(mut self, presence: UpdatePresencePayload) -> Self {
+        self.gateway = self.gateway.presence(presence);
+        self
+    }
+
+    /// Set the proxy to use for HTTP requests
+    ///
+    /// If `use_http` is `true`, HTTP is used instead of HTTPS to connect to the
+    /// proxy
+    pub fn proxy<T: Into<String>>(mut self, proxy_url: T, use_http: bool) -> Self {
+        self.http = self.http.proxy(proxy_url.into(), use_http);
+        self
+    }
+
+    /// Set the store to load the shards' sessions from, so that they resume
+    /// the sessions instead of identifying again
+    ///
+    /// Sessions are only resumed for shards with the same ID and total number
+    /// of shards, save them with [`Shards::close_and_save_sessions`] when
+    /// shutting down
+    pub fn session_store<T: SessionStore + 'static>(mut self, session_store: T) -> Self {
+        self.session_store = Some(Box::new(session_store));
+        self
+    }
+
+    /// Set which shards to start
+    ///
+    /// Defaults to [`ShardSelection::Recommended`]
+    pub const fn shards(mut self, shards: ShardSelection) -> Self {
+        self.shards = shards;
+        self
+    }
+
+    /// Set the timeout for HTTP requests
+    ///
+    /// Defaults to 10 seconds
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http = self.http.timeout(timeout);
+        self
+    }
+}
+
+/// Which shards to start, used in [`BotBuilder::shards`]
+///
+/// Use [`ShardSelection::Range`] or [`ShardSelection::Bucket`] to split the bot
+/// across multiple processes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShardSelection {
+    /// Start the shards in the given bucket out of the total, which are every
+    /// `concurrency`th shard starting from `bucket_id`
+    ///
+    /// `bucket_id` must be less than `concurrency`, which must be less than
+    /// `total`
+    Bucket {
+        /// The ID of the bucket
+        bucket_id: u64,
+        /// The number of buckets, usually the bot's max concurrency
+        concurrency: u64,
+        /// The total number of shards across all processes
+        total: u64,
+    },
+    /// Start the shards with IDs in the given range out of the total
+    ///
+    /// For example, to start shards 8 to 15 out of 32, use `start` 8, `end` 16
+    /// and `total` 32
+    ///
+    /// `start` must be less than `end`, which must not be more than `total`
+    Range {
+        /// The ID after the last shard to start
+        end: u64,
+        /// The ID of the first shard to start
+        start: u64,
+        /// The total number of shards across all processes
+        total: u64,
+    },
+    /// Start the number of shards recommended by Discord
+    Recommended,
+    /// Start the given number of shards
+    Total(u64),
+}
+
+impl ShardSelection {
+    pub(crate) async fn create(
+        self,
+        http: &Client,
+        config: Config,
+        sessions: &HashMap<ShardId, Session>,
+    ) -> Result<Vec<Shard>, Error> {
+        if !self.is_valid() {
+            return Err(Error::InvalidShardSelection(self));
+        }
+
+        Ok(match self {
+            Self::Bucket {
+                bucket_id,
+                concurrency,
+                total,
+            } => stream::create_bucket(
+                bucket_id,
+                concurrency,
+                total,
+                config,
+                |shard_id, config_builder| shard_config(shard_id, config_builder, sessions),
+            )
+            .collect(),
+            Self::Range { end, start, total } => {
+                stream::create_range(start..end, total, config, |shard_id, config_builder| {
+                    shard_config(shard_id, config_builder, sessions)
+                })
+                .collect()
+            }
+            Self::Recommended => {
+                stream::create_recommended(http, config, |shard_id, config_builder| {
+                    shard_config(shard_id, config_builder, sessions)
+                })
+                .await?
+                .collect()
+            }
+            Self::Total(total) => {
+                stream::create_range(.., total, config, |shard_id, config_builder| {
+                    shard_config(shard_id, config_builder, sessions)
+                })
+                .collect()
+            }
+        })
+    }
+
+    /// Return the range of shards the given process should start when the
+    /// shards are split evenly across the given number of processes
+    ///
+    /// `process_id` starts from 0, when the shards can't be split evenly, the
+    /// first `total % process_count` processes start one more shard than the
+    /// others
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sparkle_convenience::builder::ShardSelection;
+    ///
+    /// assert_eq!(
+    ///     ShardSelection::for_process(1, 4, 32)?,
+    ///     ShardSelection::Range {
+    ///         end: 16,
+    ///         start: 8,
+    ///         total: 32
+    ///     }
+    /// );
+    /// # Ok::<(), sparkle_convenience::error::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidProcess`] if `process_id` isn't less than
+    /// `process_count` or `process_count` is greater than `total`
+    #[expect(
+        clippy::result_large_err,
+        reason = "the error type is shared with the rest of the library"
+    )]
+    pub const fn for_process(
+        process_id: u64,
+        process_count: u64,
+        total: u64,
+    ) -> Result<Self, Error> {
+        if process_id >= process_count || process_count > total {
+            return Err(Error::InvalidProcess {
+                process_count,
+                process_id,
+                total,
+            });
+        }
+
+        let shards_per_process = total.div_euclid(process_count);
+        let remainder = total.rem_euclid(process_count);
+        let start = process_id
+            .saturating_mul(shards_per_process)
+            .saturating_add(if process_id < remainder {
+                process_id
+            } else {
+                remainder
+            });
+        let end = start
+            .saturating_add(shards_per_process)
+            .saturating_add(if process_id < remainder { 1 } else { 0 });
+
+        Ok(Self::Range { end, start, total })
+    }
+
+    const fn is_valid(self) -> bool {
+        match self {
+            Self::Bucket {
+                bucket_id,
+                concurrency,
+                total,
+            } => bucket_id < concurrency && concurrency < total,
+            Self::Range { end, start, total } => start < end && end <= total,
+            Self::Recommended => true,
+            Self::Total(total) => total > 0,
+        }
+    }
+
+    /// Return the selection scaled to the given total, used when resharding
+    ///
+    /// The bounds of a range are scaled proportionally, so ranges that split
+    /// the shards across processes still cover all the shards without
+    /// overlapping, buckets keep their ID and concurrency and other
+    /// selections start all the shards
+    pub(crate) fn rescaled(self, new_total: u64) -> Self {
+        match self {
+            Self::Bucket {
+                bucket_id,
+                concurrency,
+                ..
+            } => Self::Bucket {
+                bucket_id,
+                concurrency,
+                total: new_total,
+            },
+            Self::Range { end, start, total } => Self::Range {
+                end: rescale(end, total, new_total),
+                start: rescale(start, total, new_total),
+                total: new_total,
+            },
+            Self::Recommended | Self::Total(_) => Self::Total(new_total),
+        }
+    }
+}
+
+/// Scale the shard ID from the old total to the new total, rounding down
+fn rescale(shard_id: u64, total: u64, new_total: u64) -> u64 {
+    u128::from(shard_id)
+        .saturating_mul(u128::from(new_total))
+        .checked_div(u128::from(total))
+        .and_then(|scaled| u64::try_from(scaled).ok())
+        .unwrap_or(new_total)
+}
+
+/// Read the bot's user ID from the token, which is its first part encoded in
+/// base64
+fn token_user_id(token: &str) -> Option<Id<UserMarker>> {
+    let encoded = token.trim_start_matches("Bot ").split('.').next()?;
+
+    let mut decoded = Vec::new();
+    let mut bits: u32 = 0;
+    let mut bit_count: u32 = 0;
+    for character in encoded.bytes().filter(|character| *character != b'=') {
+        let value = match character {
+            b'A'..=b'Z' => character.checked_sub(b'A')?,
+            b'a'..=b'z' => character.checked_sub(b'a')?.checked_add(26)?,
+            b'0'..=b'9' => character.checked_sub(b'0')?.checked_add(52)?,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+
+        bits = (bits.checked_shl(6)? | u32::from(value)) & 0xFFFF;
+        bit_count = bit_count.checked_add(6)?;
+        if bit_count >= 8 {
+            bit_count = bit_count.checked_sub(8)?;
+            decoded.push(u8::try_from(bits.checked_shr(bit_count)? & 0xFF).ok()?);
+        }
+    }
+
+    String::from_utf8(decoded)
+        .ok()?
+        .parse()
+        .ok()
+        .and_then(Id::new_checked)
+}
+
+fn shard_config(
+    shard_id: ShardId,
+    config_builder: ConfigBuilder,
+    sessions: &HashMap<ShardId, Session>,
+) -> Config {
+    match sessions.get(&shard_id) {
+        Some(session) => config_builder.session(session.clone()).build(),
+        None => config_builder.build(),
+    }
+}
@@ -0,0 +1,134 @@
+use tokio::runtime::Builder;
+use twilight_gateway::Intents;
+use twilight_model::id::Id;
+
+use crate::{
+    builder::{BotBuilder, ShardSelection, token_user_id},
+    error::Error,
+};
+
+fn process_ranges(process_count: u64, total: u64) -> Vec<ShardSelection> {
+    (0..process_count)
+        .map(|process_id| ShardSelection::for_process(process_id, process_count, total).unwrap())
+        .collect()
+}
+
+fn ranges(total: u64, bounds: &[(u64, u64)]) -> Vec<ShardSelection> {
+    bounds
+        .iter()
+        .map(|&(start, end)| ShardSelection::Range { end, start, total })
+        .collect()
+}
+
+#[test]
+fn for_process_uneven() {
+    assert_eq!(
+        process_ranges(3, 10),
+        ranges(10, &[(0, 4), (4, 7), (7, 10)])
+    );
+    assert_eq!(
+        process_ranges(4, 9),
+        ranges(9, &[(0, 3), (3, 5), (5, 7), (7, 9)])
+    );
+    assert_eq!(process_ranges(2, 2), ranges(2, &[(0, 1), (1, 2)]));
+    assert!(
+        process_ranges(4, 9)
+            .into_iter()
+            .all(ShardSelection::is_valid)
+    );
+}
+
+#[test]
+fn for_process_invalid() {
+    assert!(matches!(
+        ShardSelection::for_process(1, 2, 1),
+        Err(Error::InvalidProcess {
+            process_count: 2,
+            process_id: 1,
+            total: 1
+        })
+    ));
+    assert!(matches!(
+        ShardSelection::for_process(4, 4, 32),
+        Err(Error::InvalidProcess { .. })
+    ));
+    assert!(matches!(
+        ShardSelection::for_process(0, 0, 32),
+        Err(Error::InvalidProcess { .. })
+    ));
+}
+
+#[test]
+fn shard_selection_valid() {
+    assert!(ShardSelection::Recommended.is_valid());
+    assert!(
+        ShardSelection::Bucket {
+            bucket_id: 1,
+            concurrency: 16,
+            total: 32
+        }
+        .is_valid()
+    );
+    assert!(
+        !ShardSelection::Range {
+            end: 33,
+            start: 8,
+            total: 32
+        }
+        .is_valid()
+    );
+    assert!(!ShardSelection::Total(0).is_valid());
+}
+
+#[test]
+fn large_threshold_invalid() {
+    let build_res = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            BotBuilder::new("token", Intents::empty())
+                .large_threshold(251)
+                .build()
+                .await
+        });
+
+    assert!(matches!(build_res, Err(Error::InvalidLargeThreshold(251))));
+}
+
+#[test]
+fn token_user_id_valid() {
+    assert_eq!(
+        token_user_id("MTIzNDU2Nzg5MDEyMzQ1Njc4.GabcDe.secret"),
+        Some(Id::new(123_456_789_012_345_678))
+    );
+    assert_eq!(
+        token_user_id("Bot MTIzNDU2Nzg5MDEyMzQ1Njc4OQ==.GabcDe.secret"),
+        Some(Id::new(1_234_567_890_123_456_789))
+    );
+}
+
+#[test]
+fn token_user_id_invalid() {
+    assert_eq!(token_user_id("token"), None);
+    assert_eq!(token_user_id("dG9rZW4.GabcDe.secret"), None);
+    assert_eq!(token_user_id("MTIz!.GabcDe.secret"), None);
+}
+
+#[test]
+fn fetch_user_disabled_invalid_token() {
+    let build_res = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            BotBuilder::new("token", Intents::empty())
+                .fetch_application(false)
+                .fetch_user(false)
+                .shards(ShardSelection::Total(1))
+                .build()
+                .await
+        });
+
+    assert!(matches!(build_res, Err(Error::InvalidToken)));
+}
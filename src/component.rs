@@ -0,0 +1,249 @@
+//! Building buttons and select menus and laying them out in action rows
+
+#[cfg(test)]
+mod tests;
+
+use twilight_model::channel::message::{
+    Component,
+    ReactionType,
+    component::{ActionRow, Button, ButtonStyle, SelectMenu, SelectMenuOption},
+};
+use twilight_validate::component::{ACTION_ROW_COMPONENT_COUNT, COMPONENT_COUNT};
+
+use crate::error::ReplyValidationError;
+
+/// Builds a [`Button`]
+///
+/// Add it to a reply with [`Reply::button`], which puts it in an action row
+///
+/// [`Reply::button`]: crate::reply::Reply::button
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct ButtonBuilder(Button);
+
+impl ButtonBuilder {
+    /// Create a red button with the given custom ID
+    pub fn danger<T: Into<String>>(custom_id: T) -> Self {
+        Self::new(ButtonStyle::Danger, custom_id)
+    }
+
+    /// Disable the button
+    pub const fn disabled(mut self) -> Self {
+        self.0.disabled = true;
+        self
+    }
+
+    /// Set the emoji of the button
+    pub fn emoji(mut self, emoji: ReactionType) -> Self {
+        self.0.emoji = Some(emoji);
+        self
+    }
+
+    /// Set the label of the button
+    pub fn label<T: Into<String>>(mut self, label: T) -> Self {
+        self.0.label = Some(label.into());
+        self
+    }
+
+    /// Create a grey button that opens the given URL instead of sending an
+    /// interaction
+    pub fn link<T: Into<String>>(url: T) -> Self {
+        Self(Button {
+            custom_id: None,
+            disabled: false,
+            emoji: None,
+            label: None,
+            style: ButtonStyle::Link,
+            url: Some(url.into()),
+        })
+    }
+
+    /// Create a button with the given style and custom ID
+    ///
+    /// Use [`ButtonBuilder::link`] for link buttons
+    pub fn new<T: Into<String>>(style: ButtonStyle, custom_id: T) -> Self {
+        Self(Button {
+            custom_id: Some(custom_id.into()),
+            disabled: false,
+            emoji: None,
+            label: None,
+            style,
+            url: None,
+        })
+    }
+
+    /// Create a blurple button with the given custom ID
+    pub fn primary<T: Into<String>>(custom_id: T) -> Self {
+        Self::new(ButtonStyle::Primary, custom_id)
+    }
+
+    /// Create a grey button with the given custom ID
+    pub fn secondary<T: Into<String>>(custom_id: T) -> Self {
+        Self::new(ButtonStyle::Secondary, custom_id)
+    }
+
+    /// Create a green button with the given custom ID
+    pub fn success<T: Into<String>>(custom_id: T) -> Self {
+        Self::new(ButtonStyle::Success, custom_id)
+    }
+}
+
+impl From<ButtonBuilder> for Component {
+    fn from(builder: ButtonBuilder) -> Self {
+        Self::Button(builder.0)
+    }
+}
+
+/// Builds a [`SelectMenu`] with text options
+///
+/// Add it to a reply with [`Reply::select_menu`], which puts it in its own
+/// action row
+///
+/// User, role, channel and mentionable select menus aren't supported since
+/// Twilight's models don't support them yet
+///
+/// [`Reply::select_menu`]: crate::reply::Reply::select_menu
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct SelectMenuBuilder(SelectMenu);
+
+impl SelectMenuBuilder {
+    /// Disable the select menu
+    pub const fn disabled(mut self) -> Self {
+        self.0.disabled = true;
+        self
+    }
+
+    /// Set the maximum number of options that can be selected
+    pub const fn max_values(mut self, max_values: u8) -> Self {
+        self.0.max_values = Some(max_values);
+        self
+    }
+
+    /// Set the minimum number of options that have to be selected
+    pub const fn min_values(mut self, min_values: u8) -> Self {
+        self.0.min_values = Some(min_values);
+        self
+    }
+
+    /// Create a select menu with the given custom ID and no options
+    pub fn new<T: Into<String>>(custom_id: T) -> Self {
+        Self(SelectMenu {
+            custom_id: custom_id.into(),
+            disabled: false,
+            max_values: None,
+            min_values: None,
+            options: vec![],
+            placeholder: None,
+        })
+    }
+
+    /// Add an option with the given label and value
+    ///
+    /// Use [`SelectMenuBuilder::option_with`] to also set its description,
+    /// emoji or whether it's selected by default
+    pub fn option<T: Into<String>, U: Into<String>>(self, label: T, value: U) -> Self {
+        self.option_with(SelectMenuOption {
+            default: false,
+            description: None,
+            emoji: None,
+            label: label.into(),
+            value: value.into(),
+        })
+    }
+
+    /// Add the given option
+    pub fn option_with(mut self, option: SelectMenuOption) -> Self {
+        self.0.options.push(option);
+        self
+    }
+
+    /// Set the placeholder shown when no option is selected
+    pub fn placeholder<T: Into<String>>(mut self, placeholder: T) -> Self {
+        self.0.placeholder = Some(placeholder.into());
+        self
+    }
+}
+
+impl From<SelectMenuBuilder> for Component {
+    fn from(builder: SelectMenuBuilder) -> Self {
+        Self::SelectMenu(builder.0)
+    }
+}
+
+/// Put the components in action rows
+///
+/// Buttons are packed into rows of up to 5, select menus and text inputs are
+/// put in their own rows and action rows are kept as they are
+///
+/// # Errors
+///
+/// Returns [`ReplyValidationError::TooManyComponents`] if the components
+/// don't fit in 5 rows
+pub fn action_rows(components: Vec<Component>) -> Result<Vec<Component>, ReplyValidationError> {
+    let mut rows = vec![];
+    for component in components {
+        push_to_rows(&mut rows, component);
+    }
+
+    if rows.len() > COMPONENT_COUNT {
+        return Err(ReplyValidationError::TooManyComponents(rows.len()));
+    }
+
+    Ok(rows)
+}
+
+/// Return the components with all buttons and select menus disabled,
+/// including the ones in action rows
+///
+/// Link buttons are kept enabled since they don't send interactions, useful
+/// for menus that stopped listening for interactions
+#[must_use]
+pub fn disable_all(components: &[Component]) -> Vec<Component> {
+    components
+        .iter()
+        .map(|component| match component {
+            Component::ActionRow(row) => Component::ActionRow(ActionRow {
+                components: disable_all(&row.components),
+            }),
+            Component::Button(button) if button.style != ButtonStyle::Link => {
+                Component::Button(Button {
+                    disabled: true,
+                    ..button.clone()
+                })
+            }
+            Component::SelectMenu(select_menu) => Component::SelectMenu(SelectMenu {
+                disabled: true,
+                ..select_menu.clone()
+            }),
+            _ => component.clone(),
+        })
+        .collect()
+}
+
+/// Add the component to the last row if it's a button and the last row has
+/// space for it, adding a new row otherwise
+pub(crate) fn push_to_rows(rows: &mut Vec<Component>, component: Component) {
+    if matches!(component, Component::ActionRow(_)) {
+        rows.push(component);
+        return;
+    }
+
+    if let Component::Button(_) = component {
+        if let Some(Component::ActionRow(row)) = rows.last_mut() {
+            if row.components.len() < ACTION_ROW_COMPONENT_COUNT
+                && row
+                    .components
+                    .iter()
+                    .all(|row_component| matches!(row_component, Component::Button(_)))
+            {
+                row.components.push(component);
+                return;
+            }
+        }
+    }
+
+    rows.push(Component::ActionRow(ActionRow {
+        components: vec![component],
+    }));
+}
@@ -0,0 +1,102 @@
+use twilight_model::channel::message::{
+    Component,
+    component::{ActionRow, Button, ButtonStyle},
+};
+
+use crate::{
+    component::{ButtonBuilder, SelectMenuBuilder, action_rows, disable_all},
+    error::ReplyValidationError,
+    reply::Reply,
+};
+
+fn button(component: Component) -> Button {
+    match component {
+        Component::Button(button) => Some(button),
+        _ => None,
+    }
+    .unwrap()
+}
+
+fn row_lengths(components: &[Component]) -> Vec<usize> {
+    components
+        .iter()
+        .map(|component| match component {
+            Component::ActionRow(row) => row.components.len(),
+            _ => 0,
+        })
+        .collect()
+}
+
+#[test]
+fn button_builder() {
+    let danger = button(
+        ButtonBuilder::danger("delete")
+            .label("Delete")
+            .disabled()
+            .into(),
+    );
+    assert_eq!(danger.style, ButtonStyle::Danger);
+    assert_eq!(danger.custom_id.as_deref(), Some("delete"));
+    assert_eq!(danger.label.as_deref(), Some("Delete"));
+    assert!(danger.disabled);
+
+    let link = button(ButtonBuilder::link("https://example.com").into());
+    assert_eq!(link.style, ButtonStyle::Link);
+    assert!(link.custom_id.is_none());
+}
+
+#[test]
+fn reply_button_layout() {
+    let reply = (0..7).fold(Reply::new(), |reply, index: u8| {
+        reply.button(ButtonBuilder::primary(index.to_string()))
+    });
+    assert_eq!(row_lengths(&reply.components), vec![5, 2]);
+
+    let with_select = reply
+        .select_menu(SelectMenuBuilder::new("select").option("label", "value"))
+        .button(ButtonBuilder::secondary("after"));
+    assert_eq!(row_lengths(&with_select.components), vec![5, 2, 1, 1]);
+}
+
+#[test]
+fn action_rows_limit() {
+    let buttons = (0..25)
+        .map(|index: u8| ButtonBuilder::success(index.to_string()).into())
+        .collect::<Vec<Component>>();
+    assert_eq!(
+        row_lengths(&action_rows(buttons.clone()).unwrap()),
+        vec![5; 5]
+    );
+
+    let mut too_many = buttons;
+    too_many.push(Component::ActionRow(ActionRow { components: vec![] }));
+    assert!(matches!(
+        action_rows(too_many),
+        Err(ReplyValidationError::TooManyComponents(6))
+    ));
+}
+
+#[test]
+fn disable_all_components() {
+    let reply = Reply::new()
+        .button(ButtonBuilder::primary("confirm"))
+        .button(ButtonBuilder::link("https://example.com"))
+        .select_menu(SelectMenuBuilder::new("select").option("label", "value"));
+
+    let disabled = disable_all(&reply.components);
+    assert_eq!(row_lengths(&disabled), vec![2, 1]);
+
+    let disabled_states = disabled
+        .iter()
+        .flat_map(|row| match row {
+            Component::ActionRow(action_row) => action_row.components.clone(),
+            _ => vec![],
+        })
+        .map(|component| match component {
+            Component::Button(button) => button.disabled,
+            Component::SelectMenu(select_menu) => select_menu.disabled,
+            _ => false,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(disabled_states, vec![true, false, true]);
+}
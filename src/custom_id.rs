@@ -0,0 +1,213 @@
+//! Encoding typed state into component custom IDs and decoding it back
+
+#[cfg(test)]
+mod tests;
+
+use std::str::Chars;
+
+use twilight_model::id::Id;
+use twilight_validate::component::COMPONENT_CUSTOM_ID_LENGTH;
+
+use crate::error::Error;
+
+macro_rules! impl_custom_id_from_str {
+    ($($kind:ty),*) => {
+        $(
+            impl CustomId for $kind {
+                fn read(reader: &mut CustomIdReader<'_>) -> Option<Self> {
+                    reader.field()?.parse().ok()
+                }
+
+                fn write(&self, writer: &mut CustomIdWriter) {
+                    writer.field(&self.to_string());
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_custom_id_tuple {
+    ($($kind:ident),*) => {
+        impl<$($kind: CustomId),*> CustomId for ($($kind,)*) {
+            fn read(reader: &mut CustomIdReader<'_>) -> Option<Self> {
+                Some(($($kind::read(reader)?,)*))
+            }
+
+            #[expect(non_snake_case, reason = "the type parameters are reused as names")]
+            fn write(&self, writer: &mut CustomIdWriter) {
+                let ($($kind,)*) = self;
+                $($kind.write(writer);)*
+            }
+        }
+    };
+}
+
+/// Implemented on types that can be encoded into a component's custom ID
+///
+/// Values are encoded as fields separated by `:`, `:` and `\` in strings are
+/// escaped, so a tuple like `("ban", user_id)` is encoded as
+/// `ban:123456789012345678`
+///
+/// Implemented for strings, booleans, integers, characters, [`Id`] and tuples
+/// of up to 4 implementing types, implement it for your own types by writing
+/// and reading their fields in the same order
+///
+/// # Example
+///
+/// ```rust
+/// use sparkle_convenience::custom_id::CustomId;
+/// use twilight_model::id::{Id, marker::UserMarker};
+///
+/// let state: (String, Id<UserMarker>, u8) = ("ban".to_owned(), Id::new(1), 7);
+/// let custom_id = state.to_custom_id()?;
+/// assert_eq!(custom_id, "ban:1:7");
+///
+/// // when the component interaction arrives
+/// assert_eq!(
+///     <(String, Id<UserMarker>, u8)>::from_custom_id(&custom_id),
+///     Some(state)
+/// );
+/// # Ok::<(), sparkle_convenience::error::Error>(())
+/// ```
+pub trait CustomId: Sized {
+    /// Decode the value from a custom ID, returning `None` if it's invalid or
+    /// has extra fields
+    #[must_use]
+    fn from_custom_id(custom_id: &str) -> Option<Self> {
+        let mut reader = CustomIdReader {
+            chars: custom_id.chars(),
+            done: false,
+        };
+        let value = Self::read(&mut reader)?;
+
+        reader.done.then_some(value)
+    }
+
+    /// Read the value's fields from the reader, returning `None` if they're
+    /// invalid
+    fn read(reader: &mut CustomIdReader<'_>) -> Option<Self>;
+
+    /// Encode the value into a custom ID
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CustomIdTooLong`] if the encoded value is longer than
+    /// 100 characters, Discord's limit for custom IDs
+    #[expect(
+        clippy::result_large_err,
+        reason = "the error type is shared with the rest of the library"
+    )]
+    fn to_custom_id(&self) -> Result<String, Error> {
+        let mut writer = CustomIdWriter {
+            custom_id: String::new(),
+            first: true,
+        };
+        self.write(&mut writer);
+
+        let len = writer.custom_id.chars().count();
+        if len > COMPONENT_CUSTOM_ID_LENGTH {
+            return Err(Error::CustomIdTooLong(len));
+        }
+
+        Ok(writer.custom_id)
+    }
+
+    /// Write the value's fields to the writer
+    fn write(&self, writer: &mut CustomIdWriter);
+}
+
+/// Reads fields from a custom ID, passed to [`CustomId::read`]
+#[derive(Clone, Debug)]
+pub struct CustomIdReader<'custom_id> {
+    chars: Chars<'custom_id>,
+    done: bool,
+}
+
+impl CustomIdReader<'_> {
+    /// Read the next field, unescaping it, returns `None` if all fields were
+    /// read
+    pub fn field(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+
+        let mut field = String::new();
+        loop {
+            match self.chars.next() {
+                Some(':') => return Some(field),
+                Some('\\') => field.push(self.chars.next()?),
+                Some(character) => field.push(character),
+                None => {
+                    self.done = true;
+                    return Some(field);
+                }
+            }
+        }
+    }
+}
+
+/// Writes fields to a custom ID, passed to [`CustomId::write`]
+#[derive(Clone, Debug)]
+pub struct CustomIdWriter {
+    custom_id: String,
+    first: bool,
+}
+
+impl CustomIdWriter {
+    /// Write a field, escaping it
+    pub fn field(&mut self, field: &str) {
+        if !self.first {
+            self.custom_id.push(':');
+        }
+        self.first = false;
+
+        for character in field.chars() {
+            if matches!(character, ':' | '\\') {
+                self.custom_id.push('\\');
+            }
+            self.custom_id.push(character);
+        }
+    }
+}
+
+impl CustomId for String {
+    fn read(reader: &mut CustomIdReader<'_>) -> Option<Self> {
+        reader.field()
+    }
+
+    fn write(&self, writer: &mut CustomIdWriter) {
+        writer.field(self);
+    }
+}
+
+impl CustomId for bool {
+    fn read(reader: &mut CustomIdReader<'_>) -> Option<Self> {
+        match reader.field()?.as_str() {
+            "1" => Some(true),
+            "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn write(&self, writer: &mut CustomIdWriter) {
+        writer.field(if *self { "1" } else { "0" });
+    }
+}
+
+impl<T> CustomId for Id<T> {
+    fn read(reader: &mut CustomIdReader<'_>) -> Option<Self> {
+        Self::new_checked(u64::read(reader)?)
+    }
+
+    fn write(&self, writer: &mut CustomIdWriter) {
+        self.get().write(writer);
+    }
+}
+
+impl_custom_id_from_str!(
+    char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl_custom_id_tuple!(First, Second);
+impl_custom_id_tuple!(First, Second, Third);
+impl_custom_id_tuple!(First, Second, Third, Fourth);
@@ -0,0 +1,53 @@
+use twilight_model::id::{Id, marker::UserMarker};
+
+use crate::{custom_id::CustomId as _, error::Error};
+
+#[test]
+fn custom_id_round_trip() {
+    let value: (String, Id<UserMarker>, bool, i32) = ("a:b\\c".to_owned(), Id::new(123), true, -5);
+    let custom_id = value.to_custom_id().unwrap();
+
+    assert_eq!(custom_id, "a\\:b\\\\c:123:1:-5");
+    assert_eq!(
+        <(String, Id<UserMarker>, bool, i32)>::from_custom_id(&custom_id),
+        Some(value)
+    );
+}
+
+#[test]
+fn custom_id_round_trip_empty_fields() {
+    let pair: (String, u8) = (String::new(), 5);
+    let pair_custom_id = pair.to_custom_id().unwrap();
+
+    assert_eq!(pair_custom_id, ":5");
+    assert_eq!(<(String, u8)>::from_custom_id(&pair_custom_id), Some(pair));
+
+    let triple: (String, String, String) = (String::new(), "a".to_owned(), String::new());
+    let triple_custom_id = triple.to_custom_id().unwrap();
+
+    assert_eq!(triple_custom_id, ":a:");
+    assert_eq!(
+        <(String, String, String)>::from_custom_id(&triple_custom_id),
+        Some(triple)
+    );
+}
+
+#[test]
+fn custom_id_invalid() {
+    assert_eq!(u8::from_custom_id("256"), None);
+    assert_eq!(Id::<UserMarker>::from_custom_id("0"), None);
+    assert_eq!(bool::from_custom_id("yes"), None);
+    assert_eq!(<(u8, u8)>::from_custom_id("1"), None);
+    assert_eq!(<(u8, u8)>::from_custom_id("1:2:3"), None);
+    assert_eq!(String::from_custom_id("trailing\\"), None);
+    assert_eq!(String::from_custom_id(""), Some(String::new()));
+}
+
+#[test]
+fn custom_id_too_long() {
+    assert_eq!("a".repeat(100).to_custom_id().unwrap().len(), 100);
+    assert!(matches!(
+        "a".repeat(101).to_custom_id(),
+        Err(Error::CustomIdTooLong(101))
+    ));
+}
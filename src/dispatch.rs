@@ -0,0 +1,329 @@
+//! Handling events concurrently with a limit on how many are handled at once
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, fmt::Display, future::Future, pin::pin, sync::Arc};
+
+use futures_util::future::{Either, select};
+use tokio::{sync::Semaphore, task::JoinHandle};
+use twilight_gateway::ShardId;
+use twilight_model::{
+    gateway::event::Event,
+    id::{Id, marker::GenericMarker},
+};
+
+use crate::{
+    Bot,
+    Shards,
+    error::Error,
+    receiver::EventReceiver,
+    shutdown::{ShutdownOptions, Tasks},
+};
+
+/// Options for [`Shards::dispatch`] and [`EventReceiver::dispatch`]
+#[derive(Clone, Copy, Debug)]
+pub struct DispatchOptions<'store> {
+    /// The maximum number of events handled at once
+    ///
+    /// No more events are received until another handler finishes when this
+    /// is reached, defaults to 64
+    pub concurrency: usize,
+    /// Which events are handled in the order they were received
+    pub order: DispatchOrder,
+    /// The options to shut down with after the shutdown signal resolves
+    pub shutdown: ShutdownOptions<'store>,
+}
+
+impl Default for DispatchOptions<'_> {
+    fn default() -> Self {
+        Self {
+            concurrency: 64,
+            order: DispatchOrder::Unordered,
+            shutdown: ShutdownOptions::default(),
+        }
+    }
+}
+
+/// Which events are handled in the order they were received, used in
+/// [`DispatchOptions::order`]
+///
+/// Handlers of events with the same key wait for the previous handler to
+/// finish, handlers of events with different keys still run concurrently
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DispatchOrder {
+    /// Events in the same channel are handled in order
+    ///
+    /// Events that aren't in a channel are handled concurrently
+    Channel,
+    /// Events in the same guild are handled in order
+    ///
+    /// Events that aren't in a guild are handled concurrently
+    Guild,
+    /// All events are handled concurrently
+    #[default]
+    Unordered,
+}
+
+impl DispatchOrder {
+    fn key(self, event: &Event) -> Option<Id<GenericMarker>> {
+        match self {
+            Self::Channel => event_channel_id(event),
+            Self::Guild => event.guild_id().map(Id::cast),
+            Self::Unordered => None,
+        }
+    }
+}
+
+/// Spawns handlers, running at most as many at once as there are permits and
+/// running handlers with the same key in order
+#[derive(Debug)]
+struct HandlerSpawner {
+    ordered_handlers: HashMap<Id<GenericMarker>, JoinHandle<()>>,
+    permits: Arc<Semaphore>,
+}
+
+impl HandlerSpawner {
+    fn new(concurrency: usize) -> Self {
+        Self {
+            ordered_handlers: HashMap::new(),
+            permits: Arc::new(Semaphore::new(concurrency.clamp(1, Semaphore::MAX_PERMITS))),
+        }
+    }
+
+    /// Wait for a permit and spawn the handler
+    ///
+    /// If a handler with the same key is still running, the handler is
+    /// spawned right away and waits for the previous handler to finish before
+    /// taking a permit instead, so that handlers waiting for their turn don't
+    /// take up the concurrency limit
+    async fn spawn<F: Future<Output = ()> + Send + 'static>(
+        &mut self,
+        tasks: &Tasks,
+        key: Option<Id<GenericMarker>>,
+        handler: F,
+    ) {
+        self.ordered_handlers
+            .retain(|_, handle| !handle.is_finished());
+        let previous = key.and_then(|order_key| self.ordered_handlers.remove(&order_key));
+
+        let handle = if let Some(previous_handle) = previous {
+            let permits = Arc::clone(&self.permits);
+            tasks.spawn(async move {
+                let _previous_res = previous_handle.await;
+
+                let Ok(permit) = permits.acquire_owned().await else {
+                    return;
+                };
+                handler.await;
+                drop(permit);
+            })
+        } else {
+            let Ok(permit) = Arc::clone(&self.permits).acquire_owned().await else {
+                return;
+            };
+            tasks.spawn(async move {
+                handler.await;
+                drop(permit);
+            })
+        };
+
+        if let Some(order_key) = key {
+            self.ordered_handlers.insert(order_key, handle);
+        }
+    }
+}
+
+impl EventReceiver {
+    /// Spawn a task running the handler for each event until the shutdown
+    /// signal resolves, then shut down the bot gracefully
+    ///
+    /// The tasks are spawned with [`Tasks::spawn`], so
+    /// [`ShutdownOptions::handler_timeout`] applies to them
+    ///
+    /// Events aren't received while [`DispatchOptions::concurrency`] handlers
+    /// are running, except that handlers waiting for the previous handler with
+    /// the same key to finish are spawned right away, see [`DispatchOrder`]
+    ///
+    /// If the handler returns an error, it's logged with [`Bot::log`], errors
+    /// returned in [`EventReceiver::recv`] are also logged, both in tasks
+    /// spawned with [`Tasks::spawn`]
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors [`EventReceiver::shutdown`] returns
+    ///
+    /// [`Tasks::spawn`]: crate::shutdown::Tasks::spawn
+    pub async fn dispatch<H, F, E, S>(
+        mut self,
+        bot: &Arc<Bot>,
+        options: DispatchOptions<'_>,
+        shutdown_signal: S,
+        handler: H,
+    ) -> Result<(), Error>
+    where
+        H: Fn(Arc<Bot>, ShardId, Event) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+        S: Future,
+    {
+        self.dispatch_until(bot, options, shutdown_signal, handler)
+            .await;
+
+        self.shutdown(bot, options.shutdown).await
+    }
+
+    async fn dispatch_until<H, F, E, S>(
+        &mut self,
+        bot: &Arc<Bot>,
+        options: DispatchOptions<'_>,
+        shutdown_signal: S,
+        handler: H,
+    ) where
+        H: Fn(Arc<Bot>, ShardId, Event) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+        S: Future,
+    {
+        let shared_handler = Arc::new(handler);
+        let mut spawner = HandlerSpawner::new(options.concurrency);
+        let mut pinned_shutdown_signal = pin!(shutdown_signal);
+
+        loop {
+            let next = {
+                let recv = pin!(self.recv());
+                let next_res = select(recv, pinned_shutdown_signal.as_mut()).await;
+                match next_res {
+                    Either::Left((next, _)) => next,
+                    Either::Right(_) => None,
+                }
+            };
+            let (shard_id, event) = match next {
+                Some(Ok(shard_event)) => shard_event,
+                Some(Err(err)) => {
+                    let log_bot = Arc::clone(bot);
+                    bot.tasks.spawn(async move {
+                        let _log_res = log_bot.log(&err.to_string()).await;
+                    });
+                    continue;
+                }
+                None => break,
+            };
+
+            let key = options.order.key(&event);
+            let task_bot = Arc::clone(bot);
+            let task_handler = Arc::clone(&shared_handler);
+            let spawn = pin!(spawner.spawn(&bot.tasks, key, async move {
+                let kind = event.kind();
+                let handle_res = task_handler(Arc::clone(&task_bot), shard_id, event).await;
+                let err_message = handle_res.err().map(|err| {
+                    format!(
+                        "error handling {} event on shard {shard_id}: {err}",
+                        kind.name().unwrap_or("unknown")
+                    )
+                });
+
+                if let Some(message) = err_message {
+                    let _log_res = task_bot.log(&message).await;
+                }
+            }));
+            let spawn_res = select(spawn, pinned_shutdown_signal.as_mut()).await;
+            if let Either::Right(_) = spawn_res {
+                break;
+            }
+        }
+    }
+}
+
+impl Shards {
+    /// Receive the events of the shards in their own tasks and spawn a task
+    /// running the handler for each event until the shutdown signal resolves,
+    /// then shut down the bot gracefully
+    ///
+    /// Use [`Shards::into_receiver`] and [`EventReceiver::dispatch`] instead to
+    /// also get a [`ShardSender`] for sending gateway commands
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn dispatch(
+    /// #     bot: std::sync::Arc<sparkle_convenience::Bot>,
+    /// #     shards: sparkle_convenience::Shards,
+    /// # ) -> Result<(), sparkle_convenience::error::Error> {
+    /// # let shutdown_signal = std::future::pending::<()>();
+    /// use sparkle_convenience::dispatch::{DispatchOptions, DispatchOrder};
+    /// use twilight_model::gateway::event::Event;
+    ///
+    /// shards
+    ///     .dispatch(
+    ///         &bot,
+    ///         DispatchOptions {
+    ///             order: DispatchOrder::Channel,
+    ///             ..DispatchOptions::default()
+    ///         },
+    ///         // for example `tokio::signal::ctrl_c()`
+    ///         shutdown_signal,
+    ///         |bot, _shard_id, event| async move {
+    ///             if let Event::MessageCreate(message) = event {
+    ///                 bot.http
+    ///                     .create_message(message.channel_id)
+    ///                     .content("hi")?
+    ///                     .await?;
+    ///             }
+    ///             Ok::<(), sparkle_convenience::error::Error>(())
+    ///         },
+    ///     )
+    ///     .await
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors [`EventReceiver::dispatch`] returns
+    ///
+    /// [`ShardSender`]: crate::receiver::ShardSender
+    pub async fn dispatch<H, F, E, S>(
+        self,
+        bot: &Arc<Bot>,
+        options: DispatchOptions<'_>,
+        shutdown_signal: S,
+        handler: H,
+    ) -> Result<(), Error>
+    where
+        H: Fn(Arc<Bot>, ShardId, Event) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+        S: Future,
+    {
+        let (events, _) = self.into_receiver(bot);
+
+        events
+            .dispatch(bot, options, shutdown_signal, handler)
+            .await
+    }
+}
+
+fn event_channel_id(event: &Event) -> Option<Id<GenericMarker>> {
+    let channel_id = match event {
+        Event::ChannelCreate(channel) => channel.id,
+        Event::ChannelDelete(channel) => channel.id,
+        Event::ChannelPinsUpdate(pins) => pins.channel_id,
+        Event::ChannelUpdate(channel) => channel.id,
+        Event::InteractionCreate(interaction) => interaction.channel.as_ref()?.id,
+        Event::MessageCreate(message) => message.channel_id,
+        Event::MessageDelete(message) => message.channel_id,
+        Event::MessageDeleteBulk(messages) => messages.channel_id,
+        Event::MessageUpdate(message) => message.channel_id,
+        Event::ReactionAdd(reaction) => reaction.channel_id,
+        Event::ReactionRemove(reaction) => reaction.channel_id,
+        Event::ReactionRemoveAll(reactions) => reactions.channel_id,
+        Event::ReactionRemoveEmoji(reactions) => reactions.channel_id,
+        Event::ThreadCreate(thread) => thread.id,
+        Event::ThreadDelete(thread) => thread.id,
+        Event::ThreadUpdate(thread) => thread.id,
+        Event::TypingStart(typing) => typing.channel_id,
+        _ => return None,
+    };
+
+    Some(channel_id.cast())
+}
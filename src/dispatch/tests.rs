@@ -0,0 +1,146 @@
+use std::{
+    sync::{
+        Arc,
+        Mutex,
+        PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::{
+    runtime::Builder,
+    sync::Notify,
+    time::{sleep, timeout},
+};
+use twilight_model::{
+    gateway::{event::Event, payload::incoming::MessageDelete},
+    id::Id,
+};
+
+use crate::{
+    dispatch::{DispatchOrder, HandlerSpawner},
+    shutdown::Tasks,
+};
+
+#[test]
+fn dispatch_order_key() {
+    let event = Event::MessageDelete(MessageDelete {
+        channel_id: Id::new(1),
+        guild_id: Some(Id::new(2)),
+        id: Id::new(3),
+    });
+
+    assert_eq!(DispatchOrder::Channel.key(&event), Some(Id::new(1)));
+    assert_eq!(DispatchOrder::Guild.key(&event), Some(Id::new(2)));
+    assert_eq!(DispatchOrder::Unordered.key(&event), None);
+    assert_eq!(
+        DispatchOrder::Channel.key(&Event::GatewayHeartbeatAck),
+        None
+    );
+}
+
+#[test]
+fn handler_spawner_order_and_concurrency() {
+    let tasks = Tasks::default();
+    let finished = Arc::new(Mutex::new(vec![]));
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+
+    Builder::new_current_thread()
+        .enable_time()
+        .start_paused(true)
+        .build()
+        .unwrap()
+        .block_on(async {
+            let mut spawner = HandlerSpawner::new(2);
+            let handlers: [(Option<u64>, u64, u8); 5] = [
+                (Some(1), 30, 1),
+                (Some(1), 0, 2),
+                (Some(1), 10, 3),
+                (Some(2), 20, 4),
+                (None, 20, 5),
+            ];
+
+            for (key, sleep_millis, number) in handlers {
+                let handler_finished = Arc::clone(&finished);
+                let handler_running = Arc::clone(&running);
+                let handler_max_running = Arc::clone(&max_running);
+                spawner
+                    .spawn(&tasks, key.map(Id::new), async move {
+                        let now_running = handler_running.fetch_add(1, Ordering::SeqCst) + 1;
+                        handler_max_running.fetch_max(now_running, Ordering::SeqCst);
+                        sleep(Duration::from_millis(sleep_millis)).await;
+                        handler_finished
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .push(number);
+                        handler_running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                    .await;
+            }
+
+            tasks.wait(Some(Duration::from_secs(1))).await;
+        });
+
+    let finished_order = finished
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    let position = |number| {
+        finished_order
+            .iter()
+            .position(|finished_number| *finished_number == number)
+    };
+    assert_eq!(finished_order.len(), 5);
+    assert!(position(1) < position(2));
+    assert!(position(2) < position(3));
+    assert!(position(4) < position(1));
+    assert_eq!(max_running.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn handler_spawner_waits_for_permit() {
+    let tasks = Tasks::default();
+
+    Builder::new_current_thread()
+        .enable_time()
+        .start_paused(true)
+        .build()
+        .unwrap()
+        .block_on(async {
+            let mut spawner = HandlerSpawner::new(1);
+            let release = Arc::new(Notify::new());
+
+            let handler_release = Arc::clone(&release);
+            spawner
+                .spawn(&tasks, Some(Id::new(1)), async move {
+                    handler_release.notified().await;
+                })
+                .await;
+
+            timeout(
+                Duration::from_secs(1),
+                spawner.spawn(&tasks, Some(Id::new(1)), async {}),
+            )
+            .await
+            .unwrap();
+
+            let unkeyed_res = timeout(
+                Duration::from_secs(1),
+                spawner.spawn(&tasks, None, async {}),
+            )
+            .await;
+            assert!(unkeyed_res.is_err());
+
+            release.notify_one();
+            timeout(
+                Duration::from_secs(1),
+                spawner.spawn(&tasks, None, async {}),
+            )
+            .await
+            .unwrap();
+
+            tasks.wait(Some(Duration::from_secs(1))).await;
+        });
+}
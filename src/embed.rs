@@ -0,0 +1,358 @@
+//! Building embeds and splitting the ones that are too long for Discord
+
+#[cfg(test)]
+mod tests;
+
+use twilight_model::{
+    channel::message::{
+        Embed,
+        embed::{EmbedAuthor, EmbedField, EmbedFooter, EmbedImage, EmbedThumbnail},
+    },
+    util::Timestamp,
+};
+use twilight_validate::{
+    embed::{self, DESCRIPTION_LENGTH, EMBED_TOTAL_LENGTH, EmbedValidationError, FIELD_COUNT},
+    message::EMBED_COUNT_LIMIT,
+};
+
+use crate::reply::split::split_text_bytes;
+
+/// Builds an [`Embed`] without having to fill every field
+///
+/// Use [`EmbedBuilder::build`] to validate the embed or
+/// [`Reply::embed_with`] to add it to a reply
+///
+/// # Example
+///
+/// ```rust
+/// use sparkle_convenience::embed::EmbedBuilder;
+///
+/// let embed = EmbedBuilder::new()
+///     .title("Status")
+///     .description("All systems operational")
+///     .color(0x00_FF_00)
+///     .inline_field("Shards", "4")
+///     .inline_field("Guilds", "1200")
+///     .footer("Updated every minute")
+///     .build()?;
+///
+/// assert_eq!(embed.fields.len(), 2);
+/// # Ok::<(), twilight_validate::embed::EmbedValidationError>(())
+/// ```
+///
+/// [`Reply::embed_with`]: crate::reply::Reply::embed_with
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[must_use]
+pub struct EmbedBuilder {
+    author_icon_url: Option<String>,
+    author_name: Option<String>,
+    author_url: Option<String>,
+    color: Option<u32>,
+    description: Option<String>,
+    fields: Vec<EmbedField>,
+    footer_icon_url: Option<String>,
+    footer_text: Option<String>,
+    image_url: Option<String>,
+    thumbnail_url: Option<String>,
+    timestamp: Option<Timestamp>,
+    title: Option<String>,
+    url: Option<String>,
+}
+
+impl EmbedBuilder {
+    /// Set the name of the author of the embed
+    pub fn author<T: Into<String>>(mut self, name: T) -> Self {
+        self.author_name = Some(name.into());
+        self
+    }
+
+    /// Set the icon URL of the author, only used if [`EmbedBuilder::author`]
+    /// is called
+    pub fn author_icon_url<T: Into<String>>(mut self, icon_url: T) -> Self {
+        self.author_icon_url = Some(icon_url.into());
+        self
+    }
+
+    /// Set the URL of the author, only used if [`EmbedBuilder::author`] is
+    /// called
+    pub fn author_url<T: Into<String>>(mut self, url: T) -> Self {
+        self.author_url = Some(url.into());
+        self
+    }
+
+    /// Validate the embed and return it
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmbedValidationError`] if the embed is invalid, for example
+    /// if it's too long, [`split_embed`] can be used to split it instead
+    pub fn build(self) -> Result<Embed, EmbedValidationError> {
+        let embed = Embed::from(self);
+        embed::embed(&embed)?;
+
+        Ok(embed)
+    }
+
+    /// Set the color of the embed, for example `0xff_00_00` for red
+    pub const fn color(mut self, color: u32) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Set the description of the embed
+    pub fn description<T: Into<String>>(mut self, description: T) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a field to the embed
+    pub fn field<T: Into<String>, U: Into<String>>(mut self, name: T, value: U) -> Self {
+        self.fields.push(EmbedField {
+            inline: false,
+            name: name.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Set the text of the footer of the embed
+    pub fn footer<T: Into<String>>(mut self, text: T) -> Self {
+        self.footer_text = Some(text.into());
+        self
+    }
+
+    /// Set the icon URL of the footer, only used if [`EmbedBuilder::footer`]
+    /// is called
+    pub fn footer_icon_url<T: Into<String>>(mut self, icon_url: T) -> Self {
+        self.footer_icon_url = Some(icon_url.into());
+        self
+    }
+
+    /// Set the URL of the image of the embed
+    pub fn image<T: Into<String>>(mut self, url: T) -> Self {
+        self.image_url = Some(url.into());
+        self
+    }
+
+    /// Add an inline field to the embed, shown next to the other inline fields
+    pub fn inline_field<T: Into<String>, U: Into<String>>(mut self, name: T, value: U) -> Self {
+        self.fields.push(EmbedField {
+            inline: true,
+            name: name.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Create a new, empty embed builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the URL of the thumbnail of the embed
+    pub fn thumbnail<T: Into<String>>(mut self, url: T) -> Self {
+        self.thumbnail_url = Some(url.into());
+        self
+    }
+
+    /// Set the timestamp of the embed, shown next to the footer
+    pub const fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Set the title of the embed
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the URL the title of the embed links to
+    pub fn url<T: Into<String>>(mut self, url: T) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+}
+
+impl From<EmbedBuilder> for Embed {
+    fn from(builder: EmbedBuilder) -> Self {
+        Self {
+            author: builder.author_name.map(|name| EmbedAuthor {
+                icon_url: builder.author_icon_url,
+                name,
+                proxy_icon_url: None,
+                url: builder.author_url,
+            }),
+            color: builder.color,
+            description: builder.description,
+            fields: builder.fields,
+            footer: builder.footer_text.map(|text| EmbedFooter {
+                icon_url: builder.footer_icon_url,
+                proxy_icon_url: None,
+                text,
+            }),
+            image: builder.image_url.map(|url| EmbedImage {
+                height: None,
+                proxy_url: None,
+                url,
+                width: None,
+            }),
+            kind: "rich".to_owned(),
+            provider: None,
+            thumbnail: builder.thumbnail_url.map(|url| EmbedThumbnail {
+                height: None,
+                proxy_url: None,
+                url,
+                width: None,
+            }),
+            timestamp: builder.timestamp,
+            title: builder.title,
+            url: builder.url,
+            video: None,
+        }
+    }
+}
+
+/// Split the embeds into groups that can each be sent in one message
+///
+/// A message can have up to 10 embeds whose total length is up to 6000, use
+/// this after [`split_embed`] to send the split embeds in as few messages as
+/// possible
+///
+/// The total length is counted in bytes, the same way as
+/// [`embed::chars`], which Twilight validates embeds with before sending
+/// them
+#[must_use]
+pub fn group_embeds(embeds: Vec<Embed>) -> Vec<Vec<Embed>> {
+    let mut groups: Vec<Vec<Embed>> = vec![];
+    let mut group_len: usize = 0;
+
+    for embed in embeds {
+        let embed_len = embed::chars(&embed);
+
+        let fits = groups.last().is_some_and(|group| {
+            group.len() < EMBED_COUNT_LIMIT
+                && group_len.saturating_add(embed_len) <= EMBED_TOTAL_LENGTH
+        });
+
+        if let Some(group) = groups.last_mut().filter(|_| fits) {
+            group.push(embed);
+            group_len = group_len.saturating_add(embed_len);
+        } else {
+            groups.push(vec![embed]);
+            group_len = embed_len;
+        }
+    }
+
+    groups
+}
+
+/// Split an embed whose description, field count or total length is over
+/// Discord's limits into multiple embeds
+///
+/// The description is split at line boundaries if possible, then at word
+/// boundaries, the fields are moved to the next embed when one is full
+///
+/// The title, URL, author and thumbnail are kept in the first embed, the
+/// footer, timestamp and image are moved to the last embed and the color is
+/// kept in all of them
+///
+/// The total length is counted in bytes, the same way as [`embed::chars`],
+/// so embeds with text that isn't ASCII are split into shorter embeds
+///
+/// Returns the embed as is if it's valid, fields whose name or value are too
+/// long aren't split, so the embeds may still be invalid
+///
+/// The embeds may be too long to be sent in one message, use
+/// [`group_embeds`] to group them into messages
+#[must_use]
+pub fn split_embed(embed: Embed) -> Vec<Embed> {
+    if embed::embed(&embed).is_ok() {
+        return vec![embed];
+    }
+
+    let continuation = Embed {
+        author: None,
+        color: embed.color,
+        description: None,
+        fields: vec![],
+        footer: None,
+        image: None,
+        kind: embed.kind.clone(),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: None,
+        url: None,
+        video: None,
+    };
+    let footer_len = embed.footer.as_ref().map_or(0, |footer| footer.text.len());
+
+    let mut embeds = vec![Embed {
+        author: embed.author,
+        provider: embed.provider,
+        thumbnail: embed.thumbnail,
+        title: embed.title,
+        url: embed.url,
+        video: embed.video,
+        ..continuation.clone()
+    }];
+
+    for chunk in embed
+        .description
+        .map(|description| {
+            split_text_bytes(
+                &description,
+                DESCRIPTION_LENGTH.min(EMBED_TOTAL_LENGTH.saturating_sub(footer_len)),
+            )
+        })
+        .unwrap_or_default()
+    {
+        let chunk_len = chunk.len();
+        let fits = embeds.last().is_some_and(|current| {
+            current.description.is_none() && fits_total(current, chunk_len, footer_len)
+        });
+
+        if let Some(current) = embeds.last_mut().filter(|_| fits) {
+            current.description = Some(chunk);
+        } else {
+            embeds.push(Embed {
+                description: Some(chunk),
+                ..continuation.clone()
+            });
+        }
+    }
+
+    for field in embed.fields {
+        let field_len = field.name.len().saturating_add(field.value.len());
+        let fits = embeds.last().is_some_and(|current| {
+            current.fields.len() < FIELD_COUNT && fits_total(current, field_len, footer_len)
+        });
+
+        if let Some(current) = embeds.last_mut().filter(|_| fits) {
+            current.fields.push(field);
+        } else {
+            embeds.push(Embed {
+                fields: vec![field],
+                ..continuation.clone()
+            });
+        }
+    }
+
+    if let Some(last) = embeds.last_mut() {
+        last.footer = embed.footer;
+        last.image = embed.image;
+        last.timestamp = embed.timestamp;
+    }
+
+    embeds
+}
+
+/// Return whether text of the given length can be added to the embed while
+/// keeping space for the footer
+fn fits_total(embed: &Embed, len: usize, footer_len: usize) -> bool {
+    embed::chars(embed)
+        .saturating_add(len)
+        .saturating_add(footer_len)
+        <= EMBED_TOTAL_LENGTH
+}
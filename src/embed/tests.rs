@@ -0,0 +1,148 @@
+use twilight_model::channel::message::{
+    Embed,
+    embed::{EmbedField, EmbedFooter},
+};
+use twilight_validate::embed::{self, EMBED_TOTAL_LENGTH};
+
+use crate::embed::{EmbedBuilder, group_embeds, split_embed};
+
+fn embed(description: Option<String>, fields: Vec<EmbedField>) -> Embed {
+    Embed {
+        author: None,
+        color: Some(0xFF_00_00),
+        description,
+        fields,
+        footer: Some(EmbedFooter {
+            icon_url: None,
+            proxy_icon_url: None,
+            text: "footer".to_owned(),
+        }),
+        image: None,
+        kind: "rich".to_owned(),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: Some("title".to_owned()),
+        url: None,
+        video: None,
+    }
+}
+
+fn field(index: usize) -> EmbedField {
+    EmbedField {
+        inline: false,
+        name: format!("field {index}"),
+        value: "value".repeat(100),
+    }
+}
+
+#[test]
+fn split_embed_valid() {
+    let valid = embed(Some("description".to_owned()), vec![field(0)]);
+
+    assert_eq!(split_embed(valid.clone()), vec![valid]);
+}
+
+#[test]
+fn split_embed_description() {
+    let embeds = split_embed(embed(Some("word ".repeat(2000)), vec![]));
+
+    assert_eq!(embeds.len(), 3);
+    assert!(embeds.iter().all(|split| embed::embed(split).is_ok()));
+    assert!(embeds.iter().all(|split| split.color == Some(0xFF_00_00)));
+
+    let first = embeds.first().unwrap();
+    let last = embeds.last().unwrap();
+    assert_eq!(first.title.as_deref(), Some("title"));
+    assert!(first.footer.is_none());
+    assert!(last.title.is_none());
+    assert!(last.footer.is_some());
+}
+
+#[test]
+fn split_embed_fields() {
+    let embeds = split_embed(embed(None, (0..30).map(field).collect()));
+
+    assert!(embeds.iter().all(|split| embed::embed(split).is_ok()));
+    assert_eq!(
+        embeds.iter().map(|split| split.fields.len()).sum::<usize>(),
+        30
+    );
+    assert_eq!(
+        embeds.last().unwrap().fields.last().unwrap().name,
+        "field 29"
+    );
+}
+
+#[test]
+fn split_embed_multibyte() {
+    let fields = (0..5)
+        .map(|index: usize| EmbedField {
+            inline: false,
+            name: format!("field {index}"),
+            value: "é".repeat(1000),
+        })
+        .collect::<Vec<_>>();
+    let embeds = split_embed(embed(Some("字".repeat(3000)), fields));
+
+    assert!(embeds.len() > 1);
+    assert!(embeds.iter().all(|split| embed::embed(split).is_ok()));
+    assert_eq!(
+        embeds
+            .iter()
+            .filter_map(|split| split.description.as_deref())
+            .collect::<String>(),
+        "字".repeat(3000)
+    );
+    assert_eq!(
+        embeds.iter().map(|split| split.fields.len()).sum::<usize>(),
+        5
+    );
+}
+
+#[test]
+fn group_embeds_limits() {
+    let embeds = split_embed(embed(None, (0..30).map(field).collect()));
+    let groups = group_embeds(embeds.clone());
+
+    assert!(
+        groups
+            .iter()
+            .all(|group| { group.iter().map(embed::chars).sum::<usize>() <= EMBED_TOTAL_LENGTH })
+    );
+    assert_eq!(groups.concat(), embeds);
+
+    let small = vec![embed(Some("small".to_owned()), vec![]); 15];
+    assert_eq!(
+        group_embeds(small).iter().map(Vec::len).collect::<Vec<_>>(),
+        vec![10, 5]
+    );
+}
+
+#[test]
+fn embed_builder() {
+    let built = EmbedBuilder::new()
+        .title("title")
+        .author("author")
+        .author_url("https://example.com")
+        .footer_icon_url("https://example.com/icon.png")
+        .field("name", "value")
+        .inline_field("inline", "value")
+        .build()
+        .unwrap();
+
+    assert_eq!(built.title.as_deref(), Some("title"));
+    assert_eq!(built.kind, "rich");
+    let author = built.author.unwrap();
+    assert_eq!(author.name, "author");
+    assert_eq!(author.url.as_deref(), Some("https://example.com"));
+    assert!(author.icon_url.is_none());
+    assert!(built.footer.is_none());
+    assert!(!built.fields.first().unwrap().inline);
+    assert!(built.fields.last().unwrap().inline);
+
+    EmbedBuilder::new()
+        .title("a".repeat(257))
+        .build()
+        .unwrap_err();
+}
@@ -1,19 +1,31 @@
 //! User error types and converting options to results
 
-mod http_error;
+pub(crate) mod http_error;
 #[cfg(test)]
 mod tests;
 
 use std::{
     any::type_name,
     error,
-    fmt::{self, Debug, Display, Formatter},
+    fmt::{self, Debug, Display, Formatter, Write as _},
+    io,
 };
 
-use twilight_gateway::stream;
+use twilight_gateway::{
+    ShardId,
+    error::{ReceiveMessageError, SendError},
+    stream,
+};
 use twilight_http::response::DeserializeBodyError;
 use twilight_model::guild::Permissions;
-use twilight_validate::{message::MessageValidationError, request};
+use twilight_validate::{
+    component::{COMPONENT_COUNT, COMPONENT_CUSTOM_ID_LENGTH, ComponentValidationError},
+    embed::{EMBED_TOTAL_LENGTH, EmbedValidationError},
+    message::{EMBED_COUNT_LIMIT, MessageValidationError},
+    request,
+};
+
+use crate::builder::ShardSelection;
 
 /// Errors returned in this library
 #[derive(Debug, thiserror::Error)]
@@ -21,12 +33,53 @@ pub enum Error {
     /// Tried to send an initial response for an interaction multiple times
     #[error("initial response for that interaction has already been sent")]
     AlreadyResponded,
+    /// The custom ID encoded with [`CustomId::to_custom_id`] is longer than
+    /// Discord's limit, the given number of characters long
+    ///
+    /// [`CustomId::to_custom_id`]: crate::custom_id::CustomId::to_custom_id
+    #[error("the custom ID is {0} characters long, the limit is {COMPONENT_CUSTOM_ID_LENGTH}")]
+    CustomIdTooLong(usize),
     /// A [`DeserializeBodyError`] was returned
     #[error("{0}")]
     DeserializeBody(#[from] DeserializeBodyError),
     /// A [`twilight_http::Error`] was returned
     #[error("{0}")]
     Http(#[from] twilight_http::Error),
+    /// The threshold passed to [`BotBuilder::large_threshold`] isn't between
+    /// 50 and 250
+    ///
+    /// [`BotBuilder::large_threshold`]: crate::builder::BotBuilder::large_threshold
+    #[error("the large threshold {0} isn't between 50 and 250")]
+    InvalidLargeThreshold(u64),
+    /// The process passed to [`ShardSelection::for_process`] can't be assigned
+    /// any shards, because its ID isn't less than the number of processes or
+    /// there are more processes than shards
+    ///
+    /// [`ShardSelection::for_process`]: crate::builder::ShardSelection::for_process
+    #[error("process {process_id} of {process_count} can't be assigned any of {total} shards")]
+    InvalidProcess {
+        /// The number of processes
+        process_count: u64,
+        /// The ID of the process
+        process_id: u64,
+        /// The total number of shards
+        total: u64,
+    },
+    /// The [`ShardSelection`] passed to [`BotBuilder::shards`] is invalid
+    ///
+    /// [`ShardSelection`]: crate::builder::ShardSelection
+    /// [`BotBuilder::shards`]: crate::builder::BotBuilder::shards
+    #[error("the shard selection {0:?} is invalid")]
+    InvalidShardSelection(ShardSelection),
+    /// The bot's user ID couldn't be read from the token while
+    /// [`BotBuilder::fetch_user`] was disabled
+    ///
+    /// [`BotBuilder::fetch_user`]: crate::builder::BotBuilder::fetch_user
+    #[error("the user ID couldn't be read from the token, enable `BotBuilder::fetch_user`")]
+    InvalidToken,
+    /// An [`io::Error`] was returned
+    #[error("{0}")]
+    Io(#[from] io::Error),
     /// [`Bot::log`] was called without calling [`Bot::set_logging_channel`]
     /// first
     ///
@@ -37,14 +90,99 @@ pub enum Error {
     /// A [`MessageValidationError`] was returned
     #[error("{0}")]
     MessageValidation(#[from] MessageValidationError),
+    /// The bot is missing the given permissions required to send a reply
+    ///
+    /// Only returned when [`Reply::check_permissions`] is used
+    ///
+    /// [`Reply::check_permissions`]: crate::reply::Reply::check_permissions
+    #[error("the bot is missing the permissions {0:?}")]
+    MissingPermissions(Permissions),
+    /// A [`ReceiveMessageError`] was returned
+    #[error("{0}")]
+    ReceiveMessage(#[from] ReceiveMessageError),
+    /// A [`ValidationReport`] was returned
+    #[error("{0}")]
+    ReplyValidation(#[from] ValidationReport),
     /// A [`request::ValidationError`] was returned
     #[error("{0}")]
     RequestValidation(#[from] request::ValidationError),
+    /// A [`SendError`] was returned
+    #[error("{0}")]
+    Send(#[from] SendError),
+    /// The shard with the given ID was closed because of the given fatal error,
+    /// returned in [`EventReceiver::recv`]
+    ///
+    /// The shard isn't restarted, the other shards keep running
+    ///
+    /// [`EventReceiver::recv`]: crate::receiver::EventReceiver::recv
+    #[error("the shard {shard_id} was closed fatally: {source}")]
+    ShardFatal {
+        /// The ID of the shard
+        shard_id: ShardId,
+        /// The error the shard was closed with
+        source: ReceiveMessageError,
+    },
     /// A [`stream::StartRecommendedError`] was returned
     #[error("{0}")]
     StartRecommended(#[from] stream::StartRecommendedError),
+    /// The shard with the given ID isn't run by this process
+    #[error("the shard {0} isn't run by this process")]
+    UnknownShard(ShardId),
 }
 
+/// A problem found in [`Reply::validate`]
+///
+/// [`Reply::validate`]: crate::reply::Reply::validate
+#[derive(Debug, thiserror::Error)]
+pub enum ReplyValidationError {
+    /// The component at the given index is invalid
+    #[error("component {index} is invalid: {source}")]
+    Component {
+        /// The index of the component
+        index: usize,
+        /// The error returned when validating the component
+        source: ComponentValidationError,
+    },
+    /// The embed at the given index is invalid
+    #[error("embed {index} is invalid: {source}")]
+    Embed {
+        /// The index of the embed
+        index: usize,
+        /// The error returned when validating the embed
+        source: EmbedValidationError,
+    },
+    /// The total length of the embeds in bytes, counted the same way as
+    /// [`embed::chars`], is over the limit
+    ///
+    /// [`embed::chars`]: twilight_validate::embed::chars
+    #[error("the embeds are {0} bytes long in total, the limit is {EMBED_TOTAL_LENGTH}")]
+    EmbedsTooLong(usize),
+    /// The reply has no content, embeds, components, attachments or stickers
+    #[error("the reply is empty")]
+    Empty,
+    /// A [`MessageValidationError`] was returned
+    #[error("{0}")]
+    Message(#[from] MessageValidationError),
+    /// A [`request::ValidationError`] was returned
+    #[error("{0}")]
+    Request(#[from] request::ValidationError),
+    /// The reply has more action rows than the limit
+    #[error("the reply has {0} action rows, the limit is {COMPONENT_COUNT}")]
+    TooManyComponents(usize),
+    /// The reply has more embeds than the limit
+    #[error("the reply has {0} embeds, the limit is {EMBED_COUNT_LIMIT}")]
+    TooManyEmbeds(usize),
+}
+
+/// All the problems found in [`Reply::validate`]
+///
+/// Displayed as one problem per line
+///
+/// [`Reply::validate`]: crate::reply::Reply::validate
+#[derive(Debug, thiserror::Error)]
+#[error("the reply is invalid:{}", report_lines(.0))]
+pub struct ValidationReport(pub Vec<ReplyValidationError>);
+
 /// Trait implemented on types that can be converted into an [`anyhow::Error`]
 #[cfg(feature = "anyhow")]
 pub trait IntoError<T>: Sized {
@@ -104,16 +242,31 @@ pub enum UserError<C> {
     Internal,
     /// The bot is missing some required permissions
     ///
-    /// `None` when the error occurred outside of
-    /// [`InteractionHandle::check_permissions`] or
-    /// [`UserError::with_permissions`] wasn't called
+    /// `None` when the error occurred outside of [`Bot::check_permissions`] or
+    /// [`UserError::from_err`] and [`UserError::with_permissions`] wasn't
+    /// called
     ///
-    /// [`InteractionHandle::check_permissions`]:
-    /// crate::interaction::InteractionHandle::check_permissions
+    /// [`Bot::check_permissions`]: crate::Bot::check_permissions
     MissingPermissions(Option<Permissions>),
 }
 
 impl<C> UserError<C> {
+    /// Creates this error from an error returned in this library
+    ///
+    /// If you use `anyhow`, use [`UserError::from_anyhow_err`] instead
+    ///
+    /// HTTP errors are checked like in [`UserError::from_http_err`],
+    /// [`Error::MissingPermissions`] is converted to
+    /// [`UserError::MissingPermissions`] with the missing permissions, returns
+    /// [`UserError::Internal`] otherwise
+    pub const fn from_err(err: &Error) -> Self {
+        match err {
+            Error::Http(http_err) => Self::from_http_err(http_err),
+            Error::MissingPermissions(permissions) => Self::MissingPermissions(Some(*permissions)),
+            _ => Self::Internal,
+        }
+    }
+
     /// Creates this error from an HTTP error
     ///
     /// If you use `anyhow`, use [`UserError::from_anyhow_err`] instead
@@ -128,7 +281,9 @@ impl<C> UserError<C> {
             http_error::Error::MissingPermissions | http_error::Error::MissingAccess => {
                 Self::MissingPermissions(None)
             }
-            http_error::Error::Unknown => Self::Internal,
+            http_error::Error::Unknown
+            | http_error::Error::UnknownChannel
+            | http_error::Error::UnknownWebhook => Self::Internal,
         }
     }
 
@@ -166,6 +321,10 @@ impl<C: Clone + Display + Debug + Send + Sync + 'static> UserError<C> {
             return Self::from_http_err(http_err);
         }
 
+        if let Some(library_err) = err.downcast_ref::<Error>() {
+            return Self::from_err(library_err);
+        }
+
         Self::Internal
     }
 }
@@ -178,3 +337,11 @@ impl<C> Display for UserError<C> {
 }
 
 impl<C: Debug> error::Error for UserError<C> {}
+
+/// Return the problems in a [`ValidationReport`], each on a new line
+fn report_lines(errors: &[ReplyValidationError]) -> String {
+    errors.iter().fold(String::new(), |mut lines, err| {
+        let _write_res = write!(lines, "\n{err}");
+        lines
+    })
+}
@@ -9,7 +9,9 @@ pub(crate) enum Error {
     MissingPermissions,
     ReactionBlocked,
     Unknown,
+    UnknownChannel,
     UnknownMessage,
+    UnknownWebhook,
 }
 
 impl Error {
@@ -25,7 +27,9 @@ impl Error {
         };
 
         match code {
+            10003 => Self::UnknownChannel,
             10008 => Self::UnknownMessage,
+            10015 => Self::UnknownWebhook,
             50001 => Self::MissingAccess,
             50007 => Self::FailedDm,
             50013 => Self::MissingPermissions,
@@ -40,3 +40,34 @@ fn user_err_downcast() {
 }
 
 const fn _user_err_no_custom(_: UserError<NoCustomError>) {}
+
+#[test]
+fn user_err_from_err() {
+    use twilight_model::guild::Permissions;
+
+    use crate::error::Error;
+
+    let missing_perms =
+        UserError::<NoCustomError>::from_err(&Error::MissingPermissions(Permissions::EMBED_LINKS));
+    assert!(matches!(
+        missing_perms,
+        UserError::MissingPermissions(Some(Permissions::EMBED_LINKS))
+    ));
+
+    let internal = UserError::<NoCustomError>::from_err(&Error::LoggingWebhookMissing);
+    assert!(matches!(internal, UserError::Internal));
+}
+
+#[test]
+fn validation_report_display() {
+    use crate::error::{ReplyValidationError, ValidationReport};
+
+    assert_eq!(
+        ValidationReport(vec![
+            ReplyValidationError::Empty,
+            ReplyValidationError::TooManyEmbeds(11)
+        ])
+        .to_string(),
+        "the reply is invalid:\nthe reply is empty\nthe reply has 11 embeds, the limit is 10"
+    );
+}
@@ -0,0 +1,155 @@
+//! Creating URLs to invite the bot to guilds
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    borrow::Borrow,
+    fmt::{self, Display, Formatter},
+};
+
+use twilight_model::{
+    guild::Permissions,
+    id::{
+        Id,
+        marker::{ApplicationMarker, GuildMarker},
+    },
+};
+
+use crate::{
+    Bot,
+    reply::{PermissionContext, Reply},
+};
+
+/// A URL to invite the bot with, created with [`Bot::invite_url`]
+///
+/// Use its display implementation to get the URL
+///
+/// # Example
+///
+/// ```rust
+/// use sparkle_convenience::invite::InviteUrl;
+/// use twilight_model::{guild::Permissions, id::Id, oauth::scope};
+///
+/// let url = InviteUrl::new(
+///     Id::new(1),
+///     Permissions::SEND_MESSAGES,
+///     &[scope::BOT, scope::APPLICATIONS_COMMANDS],
+/// )
+/// .permissions(Permissions::EMBED_LINKS)
+/// .guild(Id::new(2))
+/// .disable_guild_select();
+///
+/// assert_eq!(
+///     url.to_string(),
+///     "https://discord.com/oauth2/authorize?client_id=1&permissions=18432\
+///     &scope=bot%20applications.commands&guild_id=2&disable_guild_select=true"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct InviteUrl {
+    application_id: Id<ApplicationMarker>,
+    disable_guild_select: bool,
+    guild_id: Option<Id<GuildMarker>>,
+    permissions: Permissions,
+    scopes: Vec<String>,
+}
+
+impl InviteUrl {
+    /// Add all the given permissions to the requested permissions, for
+    /// example the permissions required by each of the bot's commands
+    pub fn all_permissions<I: IntoIterator<Item = Permissions>>(self, permissions: I) -> Self {
+        permissions.into_iter().fold(self, Self::permissions)
+    }
+
+    /// Prevent the user from changing the guild selected with
+    /// [`InviteUrl::guild`]
+    pub const fn disable_guild_select(mut self) -> Self {
+        self.disable_guild_select = true;
+        self
+    }
+
+    /// Pre-select the guild to invite the bot to
+    pub const fn guild(mut self, guild_id: Id<GuildMarker>) -> Self {
+        self.guild_id = Some(guild_id);
+        self
+    }
+
+    /// Create an invite URL for the given application with the given
+    /// permissions and scopes
+    ///
+    /// Prefer [`Bot::invite_url`] to use the bot's application ID, the scopes
+    /// are in [`twilight_model::oauth::scope`]
+    pub fn new(
+        application_id: Id<ApplicationMarker>,
+        permissions: Permissions,
+        scopes: &[&str],
+    ) -> Self {
+        Self {
+            application_id,
+            disable_guild_select: false,
+            guild_id: None,
+            permissions,
+            scopes: scopes.iter().map(|scope| (*scope).to_owned()).collect(),
+        }
+    }
+
+    /// Add the given permissions to the requested permissions
+    ///
+    /// Use [`InviteUrl::all_permissions`] or [`InviteUrl::reply_permissions`]
+    /// to request the permissions required by multiple commands
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions.insert(permissions);
+        self
+    }
+
+    /// Add the permissions required to send each of the given replies in the
+    /// given context to the requested permissions, for example the replies
+    /// the bot's commands send
+    ///
+    /// See [`Reply::required_permissions`] for how the permissions are
+    /// calculated
+    pub fn reply_permissions<R: Borrow<Reply>, I: IntoIterator<Item = R>>(
+        self,
+        replies: I,
+        context: PermissionContext<'_>,
+    ) -> Self {
+        self.all_permissions(
+            replies
+                .into_iter()
+                .map(|reply| reply.borrow().required_permissions(context)),
+        )
+    }
+}
+
+impl Display for InviteUrl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "https://discord.com/oauth2/authorize?client_id={}&permissions={}&scope={}",
+            self.application_id,
+            self.permissions.bits(),
+            self.scopes.join("%20")
+        )?;
+
+        if let Some(guild_id) = self.guild_id {
+            write!(f, "&guild_id={guild_id}")?;
+        }
+        if self.disable_guild_select {
+            f.write_str("&disable_guild_select=true")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Bot {
+    /// Return an invite URL for the bot with the given permissions and scopes
+    ///
+    /// To request the permissions of multiple commands, use
+    /// [`InviteUrl::all_permissions`] or [`InviteUrl::reply_permissions`]
+    pub fn invite_url(&self, permissions: Permissions, scopes: &[&str]) -> InviteUrl {
+        InviteUrl::new(self.application_id, permissions, scopes)
+    }
+}
@@ -0,0 +1,62 @@
+use twilight_model::{guild::Permissions, id::Id, oauth::scope};
+
+use crate::{
+    embed::EmbedBuilder,
+    invite::InviteUrl,
+    reply::{PermissionContext, Reply},
+};
+
+#[test]
+fn invite_url() {
+    assert_eq!(
+        InviteUrl::new(Id::new(1), Permissions::empty(), &[scope::BOT]).to_string(),
+        "https://discord.com/oauth2/authorize?client_id=1&permissions=0&scope=bot"
+    );
+
+    assert_eq!(
+        InviteUrl::new(Id::new(1), Permissions::SEND_MESSAGES, &[scope::BOT])
+            .permissions(Permissions::EMBED_LINKS)
+            .permissions(Permissions::SEND_MESSAGES | Permissions::ATTACH_FILES)
+            .guild(Id::new(2))
+            .to_string(),
+        "https://discord.com/oauth2/authorize?client_id=1&permissions=51200&scope=bot&guild_id=2"
+    );
+}
+
+#[test]
+fn invite_url_all_permissions() {
+    let url = InviteUrl::new(Id::new(1), Permissions::empty(), &[scope::BOT]);
+
+    assert_eq!(
+        url.clone().all_permissions([
+            Permissions::SEND_MESSAGES,
+            Permissions::EMBED_LINKS,
+            Permissions::SEND_MESSAGES | Permissions::ATTACH_FILES,
+        ]),
+        url.clone().permissions(
+            Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS | Permissions::ATTACH_FILES
+        )
+    );
+    assert_eq!(url.clone().all_permissions([]), url);
+}
+
+#[test]
+fn invite_url_reply_permissions() {
+    let replies = [
+        Reply::new().content("hi"),
+        Reply::new().embed(EmbedBuilder::new().description("embed").build().unwrap()),
+        Reply::new().content("tts").tts(),
+    ];
+
+    assert_eq!(
+        InviteUrl::new(Id::new(1), Permissions::empty(), &[scope::BOT])
+            .reply_permissions(&replies, PermissionContext::default())
+            .to_string(),
+        InviteUrl::new(
+            Id::new(1),
+            Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS | Permissions::SEND_TTS_MESSAGES,
+            &[scope::BOT]
+        )
+        .to_string()
+    );
+}
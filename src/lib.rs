@@ -1,25 +1,45 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(test)]
+mod tests;
+
+pub mod builder;
+pub mod component;
+pub mod custom_id;
+pub mod dispatch;
+pub mod embed;
 pub mod error;
-mod log;
+pub mod invite;
+pub mod log;
 pub mod message;
+pub mod permissions;
+pub mod presence;
 pub mod prettify;
+pub mod receiver;
 pub mod reply;
+pub mod reshard;
+pub mod session;
+pub mod shutdown;
+pub mod status;
 
 use std::{fmt::Debug, sync::Arc};
 
+use builder::{BotBuilder, ShardSelection};
 use error::Error;
-use twilight_gateway::{
-    ConfigBuilder,
-    EventTypeFlags,
-    Intents,
-    Shard,
-    stream,
-    stream::ShardEventStream,
-};
+use log::{LogCache, LogFallback, LogIdentity, LogQueue, LogTarget, LogWebhook};
+use regex::Regex;
+use shutdown::Tasks;
+use status::ShardTracker;
+#[cfg(feature = "cache")]
+use twilight_cache_inmemory::InMemoryCache;
+use twilight_gateway::{EventTypeFlags, Intents, Shard, stream::ShardEventStream};
 use twilight_http::Client;
 use twilight_model::{
-    id::{Id, marker::WebhookMarker},
+    gateway::event::Event,
+    id::{
+        Id,
+        marker::{ApplicationMarker, UserMarker},
+    },
     oauth::Application,
     user::CurrentUser,
 };
@@ -29,20 +49,62 @@ use twilight_model::{
 #[must_use]
 pub struct Bot {
     /// The application info of the bot
-    pub application: Application,
+    ///
+    /// `None` if [`BotBuilder::fetch_application`] was disabled
+    pub application: Option<Application>,
+    /// The ID of the bot's application
+    ///
+    /// Set from [`Bot::application`] if it was fetched, otherwise assumed to be
+    /// the bot's user ID, which is the case for most bots
+    pub application_id: Id<ApplicationMarker>,
+    /// The in-memory cache of the bot
+    ///
+    /// Updated automatically when using [`Shards::into_receiver`], call
+    /// [`Bot::update`] with each event when using [`Shards::events`]
+    #[cfg(feature = "cache")]
+    pub cache: Arc<InMemoryCache>,
     /// Twilight's HTTP client
     pub http: Arc<Client>,
-    /// The webhook to log errors using
-    pub logging_webhook: Option<(Id<WebhookMarker>, String)>,
+    /// The data learned while logging messages, such as forum threads and
+    /// recreated webhooks
+    pub log_cache: LogCache,
+    log_fallbacks: Vec<LogFallback>,
+    /// The name and avatar to log messages with
+    pub log_identity: LogIdentity,
+    log_queue: LogQueue,
+    /// Additional patterns to redact from logged messages, see [`Bot::redact`]
+    ///
+    /// Empty by default
+    pub log_redactions: Vec<Regex>,
+    /// The channels to log messages to based on their level or category, see
+    /// [`Bot::add_logging_channel`]
+    pub log_targets: Vec<LogTarget>,
+    /// The webhook to log messages using when no target in
+    /// [`Bot::log_targets`] matches them
+    pub logging_webhook: Option<LogWebhook>,
+    shard_selection: ShardSelection,
+    /// Tracks the shards' sessions and reconnects, see [`Shards::status`]
+    pub shard_tracker: ShardTracker,
+    /// The tasks spawned by the bot, finished in [`Shards::shutdown`]
+    pub tasks: Tasks,
     /// The user info of the bot
-    pub user: CurrentUser,
+    ///
+    /// `None` if [`BotBuilder::fetch_user`] was disabled
+    pub user: Option<CurrentUser>,
+    /// The ID of the bot's user
+    ///
+    /// Set from [`Bot::user`] if it was fetched, otherwise read from the token
+    pub user_id: Id<UserMarker>,
 }
 
 impl Bot {
     /// Create a new bot with the given token, intents and event types
     ///
-    /// If you need more customization, every field of [`Bot`] is public so you
-    /// can create it with a struct literal
+    /// If you need more customization, use [`BotBuilder`], most fields of
+    /// [`Bot`] are also public so you can modify them after creating it
+    ///
+    /// This starts all the shards recommended by Discord, to split the bot
+    /// across multiple processes, use [`BotBuilder::shards`]
     ///
     /// # Errors
     ///
@@ -55,38 +117,28 @@ impl Bot {
         intents: Intents,
         event_types: EventTypeFlags,
     ) -> Result<(Self, Shards), Error> {
-        let token_string = token.into();
-
-        let http = Client::new(token_string.clone());
-
-        let shards = stream::create_recommended(
-            &http,
-            ConfigBuilder::new(token_string, intents)
-                .event_types(event_types)
-                .build(),
-            |_, config_builder| config_builder.build(),
-        )
-        .await?
-        .collect::<Vec<Shard>>();
-
-        let application = http.current_user_application().await?.model().await?;
-        let user = http.current_user().await?.model().await?;
+        BotBuilder::new(token, intents)
+            .event_types(event_types)
+            .build()
+            .await
+    }
 
-        Ok((
-            Self {
-                http: Arc::new(http),
-                application,
-                user,
-                logging_webhook: None,
-            },
-            Shards(shards),
-        ))
+    /// Update [`Bot::shard_tracker`] and the cache with an event received from
+    /// the given shard
+    ///
+    /// This is done automatically when using [`Shards::into_receiver`], call
+    /// this with each event when using [`Shards::events`]
+    pub fn update(&self, shard: &Shard, event: &Event) {
+        self.shard_tracker.update(shard);
+        self.shard_tracker.track_event(shard.id(), event);
+        #[cfg(feature = "cache")]
+        self.cache.update(event);
     }
 }
 
 /// Thin wrapper over the bot's shards for abstracting event streams
 ///
-/// Returned in [`Bot::new`]
+/// Returned in [`Bot::new`] and [`BotBuilder::build`]
 #[derive(Debug)]
 pub struct Shards(pub Vec<Shard>);
 
@@ -97,6 +149,9 @@ impl Shards {
     ///
     /// This method shouldn't be called repeatedly, you should instead assign
     /// the stream to a variable and call `next` on that
+    ///
+    /// The stream borrows the shards, use [`Shards::into_receiver`] to receive
+    /// events in another task or send commands while receiving events
     pub fn events(&mut self) -> ShardEventStream<'_> {
         ShardEventStream::new(self.0.iter_mut())
     }
@@ -1,76 +1,512 @@
-use twilight_http::request::channel::webhook::ExecuteWebhook;
+//! Logging messages to a channel using webhooks, with local fallbacks when
+//! that fails
+
+mod redact;
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    fs::{self, OpenOptions},
+    io::{self, Write as _},
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+
+use twilight_http::error::ErrorType;
 use twilight_model::{
+    channel::ChannelType,
     http::attachment::Attachment,
-    id::{Id, marker::ChannelMarker},
+    id::{
+        Id,
+        marker::{ChannelMarker, WebhookMarker},
+    },
 };
+use twilight_validate::message;
 
-use crate::{Bot, error::Error};
+use crate::{
+    Bot,
+    error::{Error, http_error},
+    reply::Reply,
+};
 
-impl Bot {
-    /// Log the given message to the channel set in [`Bot::set_logging_channel`]
-    ///
-    /// If the message is too long for message content, sends an attachment with
-    /// the message instead
-    ///
-    /// # Errors
-    ///
-    /// Returns [`Error::LoggingWebhookMissing`] if [`Bot::set_logging_channel`]
-    /// wasn't called
-    ///
-    /// Returns [`Error::MessageValidation`] if the bot's username is invalid as
-    /// a webhook's username
+/// The severity of a logged message, used in [`LogRoute::Level`]
+#[expect(
+    clippy::arbitrary_source_item_ordering,
+    reason = "variants are ordered by severity"
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    /// Information useful when debugging
+    Debug,
+    /// General information
+    Info,
+    /// Something unexpected that isn't an error
+    Warn,
+    /// An error
+    Error,
+}
+
+/// Where to log messages to when logging them with the webhook fails
+///
+/// Added with [`Bot::add_log_fallback`]
+#[derive(Debug)]
+pub enum LogFallback {
+    /// Log the message using the given sink
+    Custom(Box<dyn LogSink>),
+    /// Append the message to the given file
+    File(LogFile),
+    /// Print the message to stderr
+    Stderr,
+}
+
+impl LogFallback {
+    fn log(&self, message: &str) -> io::Result<()> {
+        match self {
+            Self::Custom(sink) => sink.log(message),
+            Self::File(file) => file.log(message),
+            Self::Stderr => writeln!(io::stderr(), "{message}"),
+        }
+    }
+}
+
+/// A file to log messages to, rotated when it gets too large
+///
+/// When appending a message would make the file larger than
+/// [`LogFile::max_size`], the file is renamed to `<path>.1`, older files are
+/// shifted to `<path>.2`, `<path>.3` and so on, and files older than
+/// [`LogFile::max_backups`] are deleted
+///
+/// The file is written to synchronously since this is only used as a fallback
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogFile {
+    /// How many rotated files to keep
+    pub max_backups: u16,
+    /// The size in bytes after which the file is rotated
+    pub max_size: u64,
+    /// The path of the file
+    pub path: PathBuf,
+}
+
+impl LogFile {
+    fn backup_path(&self, index: u16) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    fn log(&self, message: &str) -> io::Result<()> {
+        let line = format!("{message}\n");
+
+        let size = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => 0,
+            Err(err) => return Err(err),
+        };
+        if size > 0
+            && size.saturating_add(u64::try_from(line.len()).unwrap_or(u64::MAX)) > self.max_size
+        {
+            self.rotate()?;
+        }
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+
+    /// Create a new log file configuration with the given path
     ///
-    /// Returns [`Error::Http`] if executing the webhook fails
-    pub async fn log(&self, message: &str) -> Result<(), Error> {
-        match self.logging_execute_webhook()?.content(message) {
-            Ok(exec_webhook) => exec_webhook.await?,
-            Err(_) => {
-                self.logging_execute_webhook()?
-                    .content(&format!(
-                        "{}...",
-                        message.chars().take(100).collect::<String>(),
-                    ))?
-                    .attachments(&[Attachment::from_bytes(
-                        "log_message.txt".to_owned(),
-                        message.to_owned().into_bytes(),
-                        0,
-                    )])?
-                    .await?
+    /// By default, the file is rotated after 8 MiB and 3 rotated files are kept
+    #[must_use]
+    pub fn new<T: Into<PathBuf>>(path: T) -> Self {
+        Self {
+            max_backups: 3,
+            max_size: 8 * 1024 * 1024,
+            path: path.into(),
+        }
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        if self.max_backups == 0 {
+            return fs::remove_file(&self.path);
+        }
+
+        let oldest = self.backup_path(self.max_backups);
+        if oldest.exists() {
+            fs::remove_file(oldest)?;
+        }
+        for index in (1..self.max_backups).rev() {
+            let backup = self.backup_path(index);
+            if backup.exists() {
+                fs::rename(backup, self.backup_path(index.saturating_add(1)))?;
             }
-        };
+        }
 
-        Ok(())
+        fs::rename(&self.path, self.backup_path(1))
     }
+}
 
-    fn logging_execute_webhook(&self) -> Result<ExecuteWebhook<'_>, Error> {
-        let (webhook_id, webhook_token) = self
-            .logging_webhook
-            .as_ref()
-            .ok_or(Error::LoggingWebhookMissing)?;
+/// A rule to match logged messages against, used in [`LogTarget`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogRoute {
+    /// Match all messages
+    All,
+    /// Match messages with the given category
+    Category(String),
+    /// Match messages with the given level or a more severe one
+    Level(LogLevel),
+}
 
-        Ok(self
-            .http
-            .execute_webhook(*webhook_id, webhook_token)
-            .username(&self.user.name)?)
+impl LogRoute {
+    fn matches(&self, level: LogLevel, category: Option<&str>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Category(route_category) => category == Some(route_category.as_str()),
+            Self::Level(route_level) => level >= *route_level,
+        }
     }
+}
 
-    /// Set the channel to log messages to
+/// A channel to log messages to, added with [`Bot::add_logging_channel`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogTarget {
+    /// The name of the target
+    pub name: String,
+    /// The rules to match messages against, messages matching any of them are
+    /// logged to this target
+    pub routes: Vec<LogRoute>,
+    /// The webhook to log messages using
+    pub webhook: LogWebhook,
+}
+
+impl LogTarget {
+    fn matches(&self, level: LogLevel, category: Option<&str>) -> bool {
+        self.routes
+            .iter()
+            .any(|route| route.matches(level, category))
+    }
+}
+
+/// Messages that couldn't be logged with the webhook because of a transient
+/// error, logged again once it's available
+///
+/// Each target has its own queue, so a target that keeps failing doesn't stop
+/// messages from being logged to the other targets
+///
+/// When the queue of a target is full, its oldest message is dropped
+///
+/// Returned in [`Bot::log_queue`], set with [`Bot::set_log_queue`]
+#[derive(Debug)]
+pub struct LogQueue {
+    capacity: usize,
+    /// The queued messages by the name of their target, `None` for the channel
+    /// set in [`Bot::set_logging_channel`]
+    targets: Mutex<HashMap<Option<String>, VecDeque<String>>>,
+}
+
+impl LogQueue {
+    /// Return whether there are no queued messages
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.targets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .all(VecDeque::is_empty)
+    }
+
+    /// Return the number of queued messages for all targets
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.targets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(VecDeque::len)
+            .sum()
+    }
+
+    /// Create a new, empty queue that holds at most the given number of
+    /// messages for each target
     ///
-    /// Uses the first webhook in the channel that's made by the bot or creates
-    /// a new one if none exist
+    /// Pass `0` to disable queueing messages
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            targets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn pop(&self, target: Option<&str>) -> Option<String> {
+        self.targets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(&target.map(ToOwned::to_owned))
+            .and_then(VecDeque::pop_front)
+    }
+
+    fn push(&self, target: Option<&str>, message: String) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut targets = self.targets.lock().unwrap_or_else(PoisonError::into_inner);
+        let messages = targets.entry(target.map(ToOwned::to_owned)).or_default();
+        while messages.len() >= self.capacity {
+            messages.pop_front();
+        }
+        messages.push_back(message);
+        drop(targets);
+    }
+
+    fn push_front(&self, target: Option<&str>, message: String) {
+        let mut targets = self.targets.lock().unwrap_or_else(PoisonError::into_inner);
+        let messages = targets.entry(target.map(ToOwned::to_owned)).or_default();
+        if messages.len() < self.capacity {
+            messages.push_front(message);
+        }
+        drop(targets);
+    }
+
+    /// Return the targets that have queued messages
+    fn targets(&self) -> Vec<Option<String>> {
+        self.targets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|(_, messages)| !messages.is_empty())
+            .map(|(target, _)| target.clone())
+            .collect()
+    }
+}
+
+impl Default for LogQueue {
+    /// Create a new queue that holds at most 100 messages for each target
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+type LogThreadKey = (Id<WebhookMarker>, String);
+
+type LogWebhookCredentials = (Id<WebhookMarker>, String);
+
+/// Data learned while logging messages
+///
+/// Set in [`Bot::log_cache`]
+///
+/// This isn't persisted, so after restarting, new threads are created in forum
+/// logging channels and deleted webhooks are recreated again
+#[derive(Debug, Default)]
+pub struct LogCache {
+    /// The threads created in forum logging channels, by the ID of the
+    /// [`LogWebhook`] and the signature of the message that created them
+    threads: Mutex<HashMap<LogThreadKey, Id<ChannelMarker>>>,
+    /// The webhooks created because the webhook with the ID of the
+    /// [`LogWebhook`] was deleted
+    webhooks: Mutex<HashMap<Id<WebhookMarker>, LogWebhookCredentials>>,
+}
+
+impl LogCache {
+    /// Forget all the created threads, so that new threads are created for
+    /// the next logged messages
+    pub fn clear_threads(&self) {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    fn insert_thread(
+        &self,
+        webhook_id: Id<WebhookMarker>,
+        signature: String,
+        thread_id: Id<ChannelMarker>,
+    ) {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((webhook_id, signature), thread_id);
+    }
+
+    fn insert_webhook(
+        &self,
+        webhook_id: Id<WebhookMarker>,
+        recreated_id: Id<WebhookMarker>,
+        recreated_token: String,
+    ) {
+        self.webhooks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(webhook_id, (recreated_id, recreated_token));
+    }
+
+    fn remove_thread(&self, webhook_id: Id<WebhookMarker>, signature: &str) {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&(webhook_id, signature.to_owned()));
+    }
+
+    fn thread(&self, webhook_id: Id<WebhookMarker>, signature: &str) -> Option<Id<ChannelMarker>> {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(webhook_id, signature.to_owned()))
+            .copied()
+    }
+
+    pub(crate) fn tokens(&self) -> Vec<String> {
+        self.webhooks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|(_, token)| token.clone())
+            .collect()
+    }
+
+    /// Return the ID and token to execute the webhook with, which are of the
+    /// recreated webhook if it was deleted
+    fn webhook(&self, webhook: &LogWebhook) -> LogWebhookCredentials {
+        self.webhooks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&webhook.id)
+            .cloned()
+            .unwrap_or_else(|| (webhook.id, webhook.token.clone()))
+    }
+}
+
+/// The name and avatar to log messages with
+///
+/// Set in [`Bot::log_identity`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogIdentity {
+    /// The avatar URL to log messages with, `None` to use the webhook's avatar
+    pub avatar_url: Option<String>,
+    /// The username to log messages with, `None` to use the bot's username, or
+    /// the webhook's name if [`BotBuilder::fetch_user`] was disabled
+    ///
+    /// [`BotBuilder::fetch_user`]: crate::builder::BotBuilder::fetch_user
+    pub username: Option<String>,
+    /// The name of the webhooks created to log messages, `None` to use `Bot
+    /// Error Logger`
+    pub webhook_name: Option<String>,
+}
+
+/// A webhook to log messages using
+///
+/// Created in [`Bot::set_logging_channel`] and [`Bot::add_logging_channel`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogWebhook {
+    /// The ID of the channel the webhook is in, used to recreate the webhook
+    /// if it's deleted
+    pub channel_id: Id<ChannelMarker>,
+    /// Whether the webhook is in a forum channel, in which case messages are
+    /// logged in threads, see [`Bot::log_with`]
+    pub forum: bool,
+    /// The ID of the webhook
+    pub id: Id<WebhookMarker>,
+    /// The thread to log messages in, `None` if messages should be logged in
+    /// the webhook's channel
+    pub thread_id: Option<Id<ChannelMarker>>,
+    /// The token of the webhook
+    pub token: String,
+}
+
+/// Implemented on custom destinations to log messages to when logging them
+/// with the webhook fails
+///
+/// Set using [`LogFallback::Custom`]
+pub trait LogSink: Debug + Send + Sync {
+    /// Log the given message
     ///
     /// # Errors
     ///
-    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if getting or
-    /// creating the logging webhook fails
+    /// Returns an error if logging the message fails, in which case the next
+    /// fallback is tried
+    fn log(&self, message: &str) -> io::Result<()>;
+}
+
+impl Bot {
+    /// Add a fallback to log messages to when logging them with the webhook
+    /// fails
+    ///
+    /// Fallbacks are tried in the order they were added until one succeeds,
+    /// see [`Bot::log_with`]
+    pub fn add_log_fallback(&mut self, fallback: LogFallback) {
+        self.log_fallbacks.push(fallback);
+    }
+
+    /// Add a channel to log messages matching any of the given routes to
+    ///
+    /// If a target with the same name exists, it's replaced
+    ///
+    /// The webhook is found or created the same way as in
+    /// [`Bot::set_logging_channel`], so the channel can also be a thread or a
+    /// forum channel
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if getting the
+    /// channel, or getting or creating the logging webhook fails
+    ///
+    /// Returns [`Error::RequestValidation`] if the webhook name in
+    /// [`Bot::log_identity`] is invalid
     ///
     /// # Panics
     ///
     /// if the webhook that was just created doesn't contain a token
-    pub async fn set_logging_channel(
+    pub async fn add_logging_channel<T: Into<String>>(
         &mut self,
+        name: T,
         channel_id: Id<ChannelMarker>,
+        routes: Vec<LogRoute>,
     ) -> Result<(), Error> {
+        let target_name = name.into();
+        let webhook = self.find_or_create_logging_webhook(channel_id).await?;
+
+        self.log_targets.retain(|target| target.name != target_name);
+        self.log_targets.push(LogTarget {
+            name: target_name,
+            routes,
+            webhook,
+        });
+
+        Ok(())
+    }
+
+    async fn find_or_create_logging_webhook(
+        &self,
+        channel_id: Id<ChannelMarker>,
+    ) -> Result<LogWebhook, Error> {
+        let channel = self.http.channel(channel_id).await?.model().await?;
+        let (webhook_channel_id, thread_id) = match (channel.kind.is_thread(), channel.parent_id) {
+            (true, Some(parent_id)) => (parent_id, Some(channel_id)),
+            _ => (channel_id, None),
+        };
+
+        let (webhook_id, webhook_token) = self
+            .find_or_create_logging_webhook_in(webhook_channel_id)
+            .await?;
+
+        Ok(LogWebhook {
+            channel_id: webhook_channel_id,
+            forum: channel.kind == ChannelType::GuildForum,
+            id: webhook_id,
+            thread_id,
+            token: webhook_token,
+        })
+    }
+
+    async fn find_or_create_logging_webhook_in(
+        &self,
+        channel_id: Id<ChannelMarker>,
+    ) -> Result<LogWebhookCredentials, Error> {
         let webhook = if let Some(webhook) = self
             .http
             .channel_webhooks(channel_id)
@@ -83,14 +519,407 @@ impl Bot {
             webhook
         } else {
             self.http
-                .create_webhook(channel_id, "Bot Error Logger")?
+                .create_webhook(
+                    channel_id,
+                    self.log_identity
+                        .webhook_name
+                        .as_deref()
+                        .unwrap_or("Bot Error Logger"),
+                )?
                 .await?
                 .model()
                 .await?
         };
 
-        self.logging_webhook = Some((webhook.id, webhook.token.unwrap()));
+        Ok((webhook.id, webhook.token.unwrap()))
+    }
+
+    /// Log the messages in [`Bot::log_queue`] using the webhook
+    ///
+    /// This is called in [`Bot::log_with`] before the message is logged, you
+    /// can also call it manually, for example after calling
+    /// [`Bot::set_logging_channel`]
+    ///
+    /// When a message fails to be logged because of a transient error, such
+    /// as [`Error::LoggingWebhookMissing`], a ratelimit, a timeout or a server
+    /// error, it and the messages after it are kept in the queue of its
+    /// target, the queues of the other targets are still logged
+    ///
+    /// Messages that fail to be logged because of any other error are dropped
+    /// from the queue, they were already passed to the fallbacks when they
+    /// were queued
+    ///
+    /// # Errors
+    ///
+    /// Returns the last error [`Bot::log_with`] returns for a queued message
+    pub async fn flush_log_queue(&self) -> Result<(), Error> {
+        let mut result = Ok(());
+
+        for target in self.log_queue.targets() {
+            let flush_res = self.flush_log_target(target.as_deref()).await;
+            if let Err(err) = flush_res {
+                result = Err(err);
+            }
+        }
+
+        result
+    }
+
+    /// Log the messages queued for the given target, stopping at the first
+    /// message that fails because of a transient error
+    async fn flush_log_target(&self, target: Option<&str>) -> Result<(), Error> {
+        let mut result = Ok(());
+
+        while let Some(message) = self.log_queue.pop(target) {
+            let log_res = self
+                .log_with_webhook(self.log_target_webhook(target), &message)
+                .await;
+            match log_res {
+                Err(err) if is_transient(&err) => {
+                    self.log_queue.push_front(target, message);
+                    return Err(err);
+                }
+                Err(err) => result = Err(err),
+                Ok(()) => {}
+            }
+        }
+
+        result
+    }
+
+    /// Log the given message with [`LogLevel::Error`] and no category
+    ///
+    /// See [`Bot::log_with`] for more
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors [`Bot::log_with`] returns
+    pub async fn log(&self, message: &str) -> Result<(), Error> {
+        self.log_with(LogLevel::Error, None, message).await
+    }
+
+    /// Return the fallbacks to log messages to when logging them with the
+    /// webhook fails, see [`Bot::add_log_fallback`]
+    ///
+    /// Empty by default
+    #[must_use]
+    pub fn log_fallbacks(&self) -> &[LogFallback] {
+        &self.log_fallbacks
+    }
+
+    /// Return the messages that couldn't be logged with the webhook, see
+    /// [`Bot::log_with`]
+    #[must_use]
+    pub const fn log_queue(&self) -> &LogQueue {
+        &self.log_queue
+    }
+
+    fn log_target_webhook(&self, name: Option<&str>) -> Option<&LogWebhook> {
+        name.and_then(|target_name| {
+            self.log_targets
+                .iter()
+                .find(|target| target.name == target_name)
+        })
+        .map(|target| &target.webhook)
+        .or(self.logging_webhook.as_ref())
+    }
+
+    /// Log the given message to the targets in [`Bot::log_targets`] whose
+    /// routes match the given level or category
+    ///
+    /// If no target matches, the message is logged to the channel set in
+    /// [`Bot::set_logging_channel`]
+    ///
+    /// # Forum Channels
+    ///
+    /// When logging to a forum channel, a post is created for each distinct
+    /// signature of the message, which is its first line, and messages with
+    /// the same signature are logged in that post, see [`Bot::log_cache`]
+    ///
+    /// Secrets in the message are redacted before it's logged anywhere, see
+    /// [`Bot::redact`]
+    ///
+    /// If the message is too long for message content, sends an attachment with
+    /// the message instead
+    ///
+    /// If the webhook was deleted, it's recreated in the same channel and the
+    /// message is logged again, see [`Bot::log_cache`]
+    ///
+    /// Messages queued in [`Bot::log_queue`] for a target are logged before
+    /// the message so that messages are logged in the order they were passed
+    /// to this method, if logging a queued message fails because of a
+    /// transient error, the message is queued after it and that error is
+    /// returned, see [`Bot::flush_log_queue`]
+    ///
+    /// # Fallbacks
+    ///
+    /// If logging the message fails, it's passed to the fallbacks in
+    /// [`Bot::log_fallbacks`] in order until one succeeds
+    ///
+    /// If the error is transient, such as [`Error::LoggingWebhookMissing`], a
+    /// ratelimit, a timeout or a server error, the message is also pushed to
+    /// the target's queue in [`Bot::log_queue`] to be logged again once the
+    /// webhook is available, the queue's size can be set with
+    /// [`Bot::set_log_queue`], messages that fail because of other errors,
+    /// such as missing permissions, are only passed to the fallbacks
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LoggingWebhookMissing`] if no target matches and
+    /// [`Bot::set_logging_channel`] wasn't called
+    ///
+    /// Returns [`Error::MessageValidation`] if the username in
+    /// [`Bot::log_identity`] or the bot's username is invalid as a webhook's
+    /// username
+    ///
+    /// Returns [`Error::Http`] if executing the webhook fails
+    ///
+    /// Returns [`Error::DeserializeBody`] if deserializing the message that
+    /// created a forum post fails
+    ///
+    /// Returns [`Error::RequestValidation`] if the webhook name in
+    /// [`Bot::log_identity`] is invalid
+    ///
+    /// If logging to multiple targets fails, the last error is returned
+    pub async fn log_with(
+        &self,
+        level: LogLevel,
+        category: Option<&str>,
+        message: &str,
+    ) -> Result<(), Error> {
+        let redacted = self.redact(message);
+
+        let mut targets = self
+            .log_targets
+            .iter()
+            .filter(|target| target.matches(level, category))
+            .map(|target| Some(target.name.as_str()))
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            targets.push(None);
+        }
+
+        let mut result = Ok(());
+        for target in targets {
+            let mut log_res = self.flush_log_target(target).await;
+            if !log_res.as_ref().is_err_and(is_transient) {
+                log_res = self
+                    .log_with_webhook(self.log_target_webhook(target), &redacted)
+                    .await;
+            }
+            if let Err(err) = log_res {
+                if is_transient(&err) {
+                    self.log_queue.push(target, redacted.clone());
+                }
+                result = Err(err);
+            }
+        }
+
+        if result.is_err() {
+            self.log_with_fallbacks(&redacted);
+        }
+
+        result
+    }
+
+    fn log_with_fallbacks(&self, message: &str) {
+        for fallback in &self.log_fallbacks {
+            if fallback.log(message).is_ok() {
+                return;
+            }
+        }
+    }
+
+    async fn log_with_webhook(
+        &self,
+        log_webhook: Option<&LogWebhook>,
+        message: &str,
+    ) -> Result<(), Error> {
+        let webhook = log_webhook.ok_or(Error::LoggingWebhookMissing)?;
+
+        let mut reply = Reply::new();
+        if let Some(username) = self
+            .log_identity
+            .username
+            .as_ref()
+            .or_else(|| self.user.as_ref().map(|user| &user.name))
+        {
+            reply = reply.username(username.clone());
+        }
+        if let Some(avatar_url) = &self.log_identity.avatar_url {
+            reply = reply.avatar_url(avatar_url.clone());
+        }
+        reply = if message::content(message).is_ok() {
+            reply.content(message)
+        } else {
+            reply
+                .content(format!(
+                    "{}...",
+                    message.chars().take(100).collect::<String>()
+                ))
+                .attachment(Attachment::from_bytes(
+                    "log_message.txt".to_owned(),
+                    message.to_owned().into_bytes(),
+                    0,
+                ))
+        };
+        if let Some(thread_id) = webhook.thread_id {
+            reply = reply.thread_id(thread_id);
+        }
+
+        let signature = log_signature(message);
+        let (webhook_id, webhook_token) = self.log_cache.webhook(webhook);
+        let log_res = self
+            .log_with_webhook_credentials(
+                webhook,
+                webhook_id,
+                &webhook_token,
+                &signature,
+                reply.clone(),
+            )
+            .await;
+        match log_res {
+            Err(Error::Http(err))
+                if matches!(
+                    http_error::Error::from_http_err(&err),
+                    http_error::Error::UnknownWebhook
+                ) =>
+            {
+                let (recreated_id, recreated_token) = self
+                    .find_or_create_logging_webhook_in(webhook.channel_id)
+                    .await?;
+                self.log_cache
+                    .insert_webhook(webhook.id, recreated_id, recreated_token.clone());
+
+                self.log_with_webhook_credentials(
+                    webhook,
+                    recreated_id,
+                    &recreated_token,
+                    &signature,
+                    reply,
+                )
+                .await
+            }
+            res => res,
+        }
+    }
+
+    async fn log_with_webhook_credentials(
+        &self,
+        webhook: &LogWebhook,
+        webhook_id: Id<WebhookMarker>,
+        webhook_token: &str,
+        signature: &str,
+        mut reply: Reply,
+    ) -> Result<(), Error> {
+        if !webhook.forum {
+            self.reply_handle(&reply)
+                .execute_webhook(webhook_id, webhook_token)
+                .await?;
+            return Ok(());
+        }
+
+        if let Some(thread_id) = self.log_cache.thread(webhook.id, signature) {
+            let thread_reply = reply.clone().thread_id(thread_id);
+            match self
+                .reply_handle(&thread_reply)
+                .execute_webhook(webhook_id, webhook_token)
+                .await
+            {
+                Err(Error::Http(err))
+                    if matches!(
+                        http_error::Error::from_http_err(&err),
+                        http_error::Error::UnknownChannel
+                    ) =>
+                {
+                    self.log_cache.remove_thread(webhook.id, signature);
+                }
+                res => return res.map(|_| ()),
+            }
+        }
+
+        reply = reply.thread_name(signature.to_owned());
+        let thread_id = self
+            .reply_handle(&reply)
+            .execute_webhook_and_wait(webhook_id, webhook_token)
+            .await?
+            .model()
+            .await?
+            .channel_id;
+        self.log_cache
+            .insert_thread(webhook.id, signature.to_owned(), thread_id);
+
+        Ok(())
+    }
+
+    /// Set the queue to push messages that couldn't be logged with the webhook
+    /// to, replacing the current one and the messages in it
+    ///
+    /// Defaults to [`LogQueue::default`], use [`LogQueue::new`] to change its
+    /// capacity
+    pub fn set_log_queue(&mut self, queue: LogQueue) {
+        self.log_queue = queue;
+    }
+
+    /// Set the channel to log messages to
+    ///
+    /// Messages are logged to this channel when no target in
+    /// [`Bot::log_targets`] matches them
+    ///
+    /// Uses the first webhook in the channel that's made by the bot or creates
+    /// a new one if none exist, named after [`LogIdentity::webhook_name`]
+    ///
+    /// The channel can be a thread, in which case the webhook is in its parent
+    /// channel, or a forum channel, in which case messages are logged in
+    /// threads, see [`Bot::log_with`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if getting the
+    /// channel, or getting or creating the logging webhook fails
+    ///
+    /// Returns [`Error::RequestValidation`] if the webhook name in
+    /// [`Bot::log_identity`] is invalid
+    ///
+    /// # Panics
+    ///
+    /// if the webhook that was just created doesn't contain a token
+    pub async fn set_logging_channel(
+        &mut self,
+        channel_id: Id<ChannelMarker>,
+    ) -> Result<(), Error> {
+        self.logging_webhook = Some(self.find_or_create_logging_webhook(channel_id).await?);
 
         Ok(())
     }
 }
+
+/// Return whether logging a message that failed with the given error should be
+/// retried later
+const fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::Http(http_err) => match http_err.kind() {
+            ErrorType::RatelimiterTicket
+            | ErrorType::RequestCanceled
+            | ErrorType::RequestError
+            | ErrorType::RequestTimedOut
+            | ErrorType::ServiceUnavailable { .. } => true,
+            ErrorType::Response { status, .. } => status.is_server_error() || status.get() == 429,
+            _ => false,
+        },
+        Error::LoggingWebhookMissing => true,
+        _ => false,
+    }
+}
+
+/// Return the first line of the message, truncated to fit in a thread name
+fn log_signature(message: &str) -> String {
+    message
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("Log")
+        .chars()
+        .take(100)
+        .collect()
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests;
+
+use std::{borrow::Cow, sync::LazyLock};
+
+use regex::{NoExpand, Regex};
+
+use crate::Bot;
+
+const REDACTED: &str = "[redacted]";
+
+/// Patterns matching bot tokens, webhook URLs, interaction tokens and `token`
+/// fields in `Debug` output, with their replacements
+static SECRET_PATTERNS: LazyLock<[(Regex, String); 4]> = LazyLock::new(|| {
+    [
+        (
+            Regex::new(r"[\w-]{24,}\.[\w-]{6}\.[\w-]{27,}").unwrap(),
+            REDACTED.to_owned(),
+        ),
+        (
+            Regex::new(r"(discord(?:app)?\.com/api(?:/v\d+)?/webhooks/\d+/)[\w-]+").unwrap(),
+            format!("${{1}}{REDACTED}"),
+        ),
+        (
+            Regex::new(r"aW50ZXJhY3Rpb246[\w-]+").unwrap(),
+            REDACTED.to_owned(),
+        ),
+        (
+            Regex::new(r#"(\btoken: (?:Some\()?")(?:[^"\\]|\\.)*""#).unwrap(),
+            format!("${{1}}{REDACTED}\""),
+        ),
+    ]
+});
+
+impl Bot {
+    /// Return the message with secrets in it replaced with `[redacted]`
+    ///
+    /// This is called in [`Bot::log`] before the message is logged anywhere
+    ///
+    /// Redacts:
+    /// - The bot's token
+    /// - The tokens of the logging webhooks
+    /// - Anything that looks like a bot token, webhook URL or interaction token
+    /// - The values of `token` fields in `Debug` output, such as that of an
+    ///   [`Interaction`]
+    /// - Matches of the patterns in [`Bot::log_redactions`]
+    ///
+    /// [`Interaction`]: twilight_model::application::interaction::Interaction
+    #[must_use]
+    pub fn redact(&self, message: &str) -> String {
+        let recreated_tokens = self.log_cache.tokens();
+
+        redact(
+            message,
+            &[
+                self.http
+                    .token()
+                    .map(|token| token.strip_prefix("Bot ").unwrap_or(token)),
+                self.logging_webhook
+                    .as_ref()
+                    .map(|webhook| webhook.token.as_str()),
+            ]
+            .into_iter()
+            .flatten()
+            .chain(
+                self.log_targets
+                    .iter()
+                    .map(|target| target.webhook.token.as_str()),
+            )
+            .chain(recreated_tokens.iter().map(String::as_str))
+            .collect::<Vec<_>>(),
+            &self.log_redactions,
+        )
+    }
+}
+
+fn redact(message: &str, known_secrets: &[&str], patterns: &[Regex]) -> String {
+    let mut redacted = message.to_owned();
+
+    for secret in known_secrets {
+        if !secret.is_empty() {
+            redacted = redacted.replace(secret, REDACTED);
+        }
+    }
+
+    for (pattern, replacement) in SECRET_PATTERNS.iter() {
+        if let Cow::Owned(replaced) = pattern.replace_all(&redacted, replacement) {
+            redacted = replaced;
+        }
+    }
+    for pattern in patterns {
+        if let Cow::Owned(replaced) = pattern.replace_all(&redacted, NoExpand(REDACTED)) {
+            redacted = replaced;
+        }
+    }
+
+    redacted
+}
@@ -0,0 +1,72 @@
+use regex::Regex;
+
+use crate::log::redact::redact;
+
+#[test]
+fn redact_known_secrets() {
+    assert_eq!(
+        redact("token is slay", &["slay", ""], &[]),
+        "token is [redacted]"
+    );
+}
+
+#[test]
+fn redact_secret_patterns() {
+    assert_eq!(
+        redact(
+            "MTA1NzM2NjQ4NDI5MjI3MDIwOA.GhY3Ao.9wV8x1SbZEN4pLqa0PnTm2ebk7yQlcXoUDjRf0",
+            &[],
+            &[]
+        ),
+        "[redacted]"
+    );
+    assert_eq!(
+        redact(
+            "https://discord.com/api/webhooks/1057366484292270208/Xk3_zQ-ab",
+            &[],
+            &[]
+        ),
+        "https://discord.com/api/webhooks/1057366484292270208/[redacted]"
+    );
+    assert_eq!(
+        redact(
+            "token: \"aW50ZXJhY3Rpb246MTA1NzM2NjQ4NDI5MjI3MDIwOA\"",
+            &[],
+            &[]
+        ),
+        "token: \"[redacted]\""
+    );
+}
+
+#[test]
+fn redact_debug_token_fields() {
+    assert_eq!(
+        redact(
+            "ExecuteWebhook { webhook_id: Id<WebhookMarker>(1), token: \"Xk3_zQ-ab\", wait: false \
+             }",
+            &[],
+            &[]
+        ),
+        "ExecuteWebhook { webhook_id: Id<WebhookMarker>(1), token: \"[redacted]\", wait: false }"
+    );
+    assert_eq!(
+        redact("Session { token: Some(\"a\\\"b\"), id: \"1\" }", &[], &[]),
+        "Session { token: Some(\"[redacted]\"), id: \"1\" }"
+    );
+    assert_eq!(
+        redact("access_token: \"slay\"", &[], &[]),
+        "access_token: \"slay\""
+    );
+}
+
+#[test]
+fn redact_custom_patterns() {
+    assert_eq!(
+        redact(
+            "password=hunter2",
+            &[],
+            &[Regex::new(r"password=(\w+)").unwrap()]
+        ),
+        "[redacted]"
+    );
+}
@@ -0,0 +1,122 @@
+use std::{env, fs, process};
+
+use twilight_model::{guild::Permissions, id::Id};
+
+use crate::{
+    error::Error,
+    log::{
+        LogFile,
+        LogLevel,
+        LogQueue,
+        LogRoute,
+        LogTarget,
+        LogWebhook,
+        is_transient,
+        log_signature,
+    },
+};
+
+#[test]
+fn log_queue_drops_oldest() {
+    let queue = LogQueue::new(2);
+
+    queue.push(None, "first".to_owned());
+    queue.push(None, "second".to_owned());
+    queue.push(None, "third".to_owned());
+
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.pop(None).unwrap(), "second");
+    assert_eq!(queue.pop(None).unwrap(), "third");
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn log_queue_disabled() {
+    let queue = LogQueue::new(0);
+
+    queue.push(None, "message".to_owned());
+    queue.push_front(None, "message".to_owned());
+
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn log_queue_targets() {
+    let queue = LogQueue::new(1);
+
+    queue.push(Some("errors"), "first".to_owned());
+    queue.push(Some("joins"), "second".to_owned());
+    queue.push(Some("errors"), "third".to_owned());
+
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.pop(Some("joins")).unwrap(), "second");
+    assert_eq!(queue.targets(), [Some("errors".to_owned())]);
+    assert_eq!(queue.pop(None), None);
+    assert_eq!(queue.pop(Some("errors")).unwrap(), "third");
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn log_transient_errors() {
+    assert!(is_transient(&Error::LoggingWebhookMissing));
+    assert!(!is_transient(&Error::CustomIdTooLong(101)));
+    assert!(!is_transient(&Error::MissingPermissions(
+        Permissions::SEND_MESSAGES
+    )));
+}
+
+#[test]
+fn log_target_routes() {
+    let target = LogTarget {
+        name: "errors".to_owned(),
+        routes: vec![
+            LogRoute::Level(LogLevel::Warn),
+            LogRoute::Category("joins".to_owned()),
+        ],
+        webhook: LogWebhook {
+            channel_id: Id::new(1),
+            forum: false,
+            id: Id::new(1),
+            thread_id: None,
+            token: String::new(),
+        },
+    };
+
+    assert!(target.matches(LogLevel::Error, None));
+    assert!(target.matches(LogLevel::Warn, Some("audit")));
+    assert!(target.matches(LogLevel::Debug, Some("joins")));
+    assert!(!target.matches(LogLevel::Info, Some("audit")));
+    assert!(!target.matches(LogLevel::Info, None));
+}
+
+#[test]
+fn log_signature_first_line() {
+    assert_eq!(
+        log_signature("\n  error: slayed\nat main.rs"),
+        "error: slayed"
+    );
+    assert_eq!(log_signature(""), "Log");
+    assert_eq!(log_signature(&"a".repeat(150)).len(), 100);
+}
+
+#[test]
+fn log_file_rotates() {
+    let dir = env::temp_dir().join(format!("sparkle_convenience_log_{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let file = LogFile {
+        max_backups: 1,
+        max_size: 8,
+        path: dir.join("log.txt"),
+    };
+
+    file.log("first").unwrap();
+    file.log("second").unwrap();
+    file.log("third").unwrap();
+
+    assert_eq!(fs::read_to_string(&file.path).unwrap(), "third\n");
+    assert_eq!(fs::read_to_string(file.backup_path(1)).unwrap(), "second\n");
+    assert!(!file.backup_path(2).exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -1,6 +1,10 @@
 //! Convenient message, DM and webhook handling
 
 mod delete_after;
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
 
 use serde::de::DeserializeOwned;
 use twilight_http::{
@@ -9,19 +13,21 @@ use twilight_http::{
     response::{DeserializeBodyError, marker::EmptyBody},
 };
 use twilight_model::{
-    channel::Message,
+    channel::{Message, message::Component},
+    guild::Permissions,
     id::{
         Id,
-        marker::{ChannelMarker, MessageMarker, UserMarker, WebhookMarker},
+        marker::{ChannelMarker, MessageMarker, StickerMarker, UserMarker, WebhookMarker},
     },
 };
 use twilight_validate::message::MessageValidationError;
 
 use crate::{
     Bot,
+    component,
     error::{Error, UserError},
     message::delete_after::{DeleteParamsMessage, DeleteParamsUnknown, DeleteParamsWebhook},
-    reply::{MissingMessageReferenceHandleMethod, Reply},
+    reply::{MissingMessageReferenceHandleMethod, PermissionContext, Reply},
 };
 
 impl Bot {
@@ -42,52 +48,67 @@ pub struct ReplyHandle<'bot> {
 }
 
 impl ReplyHandle<'_> {
+    async fn check_permissions(&self, channel_id: Id<ChannelMarker>) -> Result<(), Error> {
+        let channel = self.bot.channel(channel_id).await?;
+        let Some(guild_id) = channel.guild_id else {
+            return Ok(());
+        };
+
+        let permissions = self
+            .bot
+            .channel_permissions(&channel, self.bot.user_id)
+            .await?;
+        let guild_sticker_ids = if self.reply.sticker_ids.is_empty() {
+            HashSet::new()
+        } else {
+            self.bot.guild_sticker_ids(guild_id).await?
+        };
+
+        missing_permissions(
+            self.reply,
+            permissions,
+            &guild_sticker_ids,
+            channel.kind.is_thread(),
+        )
+    }
+
     /// Send a message using this reply
     ///
     /// # Errors
     ///
+    /// Returns [`Error::MissingPermissions`] if [`Reply::check_permissions`]
+    /// was called and the bot is missing permissions required to send the
+    /// reply
+    ///
     /// Returns [`Error::MessageValidation`] if the reply is invalid (Refer to
     /// [`CreateMessage`])
     ///
-    /// Returns [`Error::Http`] if creating the message fails
+    /// Returns [`Error::Http`] if creating the message fails, or if getting
+    /// the permissions fails when [`Reply::check_permissions`] was called
+    ///
+    /// If the content is split into multiple messages with
+    /// [`Reply::content_overflow`], returns the response of the last message,
+    /// creating the messages stops at the first error
     ///
     /// [`CreateMessage`]: twilight_http::request::channel::message::create_message::CreateMessage
     pub async fn create_message(
         &self,
         channel_id: Id<ChannelMarker>,
     ) -> Result<ResponseHandle<'_, Message, DeleteParamsUnknown>, Error> {
-        let mut create_message = self.bot.http.create_message(channel_id);
-
-        if let Some(message_reference) = self.reply.message_reference {
-            create_message = create_message.reply(message_reference);
-        }
-        if let Some(allowed_mentions) = self.reply.allowed_mentions.as_ref() {
-            create_message = create_message.allowed_mentions(allowed_mentions.as_ref());
+        if self.reply.check_permissions {
+            self.check_permissions(channel_id).await?;
         }
-        if let Some(missing_reference_handle_method) =
-            self.reply.missing_message_reference_handle_method
-        {
-            create_message = create_message.fail_if_not_exists(
-                missing_reference_handle_method == MissingMessageReferenceHandleMethod::Fail,
-            );
-        }
-        if let Some(nonce) = self.reply.nonce {
-            create_message = create_message.nonce(nonce);
+
+        let mut replies = self.reply.overflow_replies().unwrap_or_default();
+        let Some(last_reply) = replies.pop() else {
+            return self.create_single_message(self.reply, channel_id).await;
+        };
+
+        for reply in &replies {
+            self.create_single_message(reply, channel_id).await?;
         }
 
-        Ok(ResponseHandle {
-            bot: self.bot,
-            delete_params: DeleteParamsUnknown,
-            response: create_message
-                .content(&self.reply.content)?
-                .embeds(&self.reply.embeds)?
-                .components(&self.reply.components)?
-                .attachments(&self.reply.attachments)?
-                .sticker_ids(&self.reply.sticker_ids)?
-                .flags(self.reply.flags)
-                .tts(self.reply.tts)
-                .await?,
-        })
+        self.create_single_message(&last_reply, channel_id).await
     }
 
     /// Send a DM using this reply
@@ -120,6 +141,98 @@ impl ReplyHandle<'_> {
         self.create_message(channel_id).await
     }
 
+    async fn create_single_message(
+        &self,
+        reply: &Reply,
+        channel_id: Id<ChannelMarker>,
+    ) -> Result<ResponseHandle<'_, Message, DeleteParamsUnknown>, Error> {
+        let mut create_message = self.bot.http.create_message(channel_id);
+
+        if let Some(message_reference) = reply.message_reference {
+            create_message = create_message.reply(message_reference);
+        }
+        if let Some(allowed_mentions) = reply.allowed_mentions.as_ref() {
+            create_message = create_message.allowed_mentions(allowed_mentions.as_ref());
+        }
+        if let Some(missing_reference_handle_method) = reply.missing_message_reference_handle_method
+        {
+            create_message = create_message.fail_if_not_exists(
+                missing_reference_handle_method == MissingMessageReferenceHandleMethod::Fail,
+            );
+        }
+        if let Some(nonce) = reply.nonce {
+            create_message = create_message.nonce(nonce);
+        }
+
+        Ok(ResponseHandle {
+            bot: self.bot,
+            delete_params: DeleteParamsUnknown,
+            response: create_message
+                .content(&reply.content)?
+                .embeds(&reply.embeds)?
+                .components(&reply.components)?
+                .attachments(&reply.attachments)?
+                .sticker_ids(&reply.sticker_ids)?
+                .flags(reply.flags)
+                .tts(reply.tts)
+                .await?,
+        })
+    }
+
+    /// Disable all the components of the given message, for example after a
+    /// menu times out
+    ///
+    /// The message is fetched to get its components, use
+    /// [`ReplyHandle::disable_message_components`] if you already have it
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors [`ReplyHandle::disable_message_components`] returns
+    ///
+    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if fetching the
+    /// message fails
+    pub async fn disable_components(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    ) -> Result<ResponseHandle<'_, Message, DeleteParamsMessage>, Error> {
+        let message = self
+            .bot
+            .http
+            .message(channel_id, message_id)
+            .await?
+            .model()
+            .await?;
+
+        self.disable_message_components(&message).await
+    }
+
+    /// Edit the given message to disable all of its components
+    ///
+    /// Unlike [`ReplyHandle::update_message`], only the components are
+    /// updated and the reply isn't used, see [`disable_all`] for which
+    /// components are disabled
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MessageValidation`] if the message's components are
+    /// invalid
+    ///
+    /// Returns [`Error::Http`] if updating the message fails
+    ///
+    /// [`disable_all`]: crate::component::disable_all
+    pub async fn disable_message_components(
+        &self,
+        message: &Message,
+    ) -> Result<ResponseHandle<'_, Message, DeleteParamsMessage>, Error> {
+        self.update_components(
+            message.channel_id,
+            message.id,
+            &component::disable_all(&message.components),
+        )
+        .await
+    }
+
     /// Execute a webhook using this reply
     ///
     /// # Errors
@@ -196,6 +309,22 @@ impl ReplyHandle<'_> {
             .tts(self.reply.tts))
     }
 
+    /// Edit the given message to remove all of its components
+    ///
+    /// Unlike [`ReplyHandle::update_message`], only the components are
+    /// updated and the reply isn't used
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Http`] if updating the message fails
+    pub async fn remove_components(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    ) -> Result<ResponseHandle<'_, Message, DeleteParamsMessage>, Error> {
+        self.update_components(channel_id, message_id, &[]).await
+    }
+
     /// Report an error returned in a message context to the user
     ///
     /// See [`UserError`] for creating the error parameter
@@ -220,15 +349,32 @@ impl ReplyHandle<'_> {
 
         match self.create_message(channel_id).await {
             Ok(message) => Ok(Some(message)),
-            Err(Error::Http(err))
-                if !matches!(UserError::<C>::from_http_err(&err), UserError::Internal) =>
-            {
-                Ok(None)
-            }
+            Err(err) if !matches!(UserError::<C>::from_err(&err), UserError::Internal) => Ok(None),
             Err(err) => Err(err),
         }
     }
 
+    async fn update_components(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+        components: &[Component],
+    ) -> Result<ResponseHandle<'_, Message, DeleteParamsMessage>, Error> {
+        Ok(ResponseHandle {
+            bot: self.bot,
+            delete_params: DeleteParamsMessage {
+                channel_id,
+                message_id,
+            },
+            response: self
+                .bot
+                .http
+                .update_message(channel_id, message_id)
+                .components(Some(components))?
+                .await?,
+        })
+    }
+
     /// Edit a message using this reply
     ///
     /// Overwrites all of the older message
@@ -364,6 +510,11 @@ impl ReplyHandle<'_> {
 ///
 /// If an error occurs when deleting the message, it's ignored since
 /// handling it would require holding the current task
+///
+/// Messages that are yet to be deleted are deleted immediately in
+/// [`Shards::shutdown`]
+///
+/// [`Shards::shutdown`]: crate::Shards::shutdown
 #[derive(Debug)]
 pub struct ResponseHandle<'bot, T, DeleteParams> {
     bot: &'bot Bot,
@@ -387,3 +538,30 @@ impl<T: DeserializeOwned + Unpin + Send, U: Send> ResponseHandle<'_, T, U> {
         self.response.model().await
     }
 }
+
+/// Return [`Error::MissingPermissions`] if the bot's permissions in the
+/// channel don't include the permissions required to send the reply
+#[expect(
+    clippy::result_large_err,
+    reason = "the error type is shared with the rest of the library"
+)]
+fn missing_permissions(
+    reply: &Reply,
+    permissions: Permissions,
+    guild_sticker_ids: &HashSet<Id<StickerMarker>>,
+    thread: bool,
+) -> Result<(), Error> {
+    let missing = reply
+        .required_permissions(PermissionContext {
+            guild_sticker_ids: Some(guild_sticker_ids),
+            thread,
+            ..PermissionContext::default()
+        })
+        .difference(permissions);
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::MissingPermissions(missing))
+    }
+}
@@ -3,8 +3,7 @@ mod tests;
 
 use std::{sync::Arc, time::Duration};
 
-use tokio::time::sleep;
-use twilight_http::{Client, response::DeserializeBodyError};
+use twilight_http::response::DeserializeBodyError;
 use twilight_model::{
     channel::Message,
     id::{
@@ -13,7 +12,7 @@ use twilight_model::{
     },
 };
 
-use crate::message::ResponseHandle;
+use crate::{Bot, message::ResponseHandle};
 
 /// Parameters for deleting a regular message
 #[expect(unnameable_types, reason = "this is a marker type")]
@@ -54,10 +53,10 @@ impl ResponseHandle<'_, Message, DeleteParamsUnknown> {
     ///
     /// Returns [`DeserializeBodyError`] if deserializing the response fails
     pub async fn delete_after(self, after: Duration) -> Result<Message, DeserializeBodyError> {
-        let http = Arc::clone(&self.bot.http);
+        let bot = self.bot;
         let message = self.model().await?;
 
-        spawn_delete(http, Params::Message(message.channel_id, message.id), after);
+        spawn_delete(bot, Params::Message(message.channel_id, message.id), after);
 
         Ok(message)
     }
@@ -68,7 +67,7 @@ impl<T> ResponseHandle<'_, T, DeleteParamsMessage> {
     #[expect(clippy::return_self_not_must_use, reason = "this is not a builder")]
     pub fn delete_after(self, after: Duration) -> Self {
         spawn_delete(
-            Arc::clone(&self.bot.http),
+            self.bot,
             Params::Message(self.delete_params.channel_id, self.delete_params.message_id),
             after,
         );
@@ -82,7 +81,7 @@ impl<T> ResponseHandle<'_, T, DeleteParamsWebhook> {
     #[expect(clippy::return_self_not_must_use, reason = "this is not a builder")]
     pub fn delete_after(self, after: Duration) -> Self {
         spawn_delete(
-            Arc::clone(&self.bot.http),
+            self.bot,
             Params::Webhook(
                 self.delete_params.webhook_id,
                 self.delete_params.token.clone(),
@@ -95,9 +94,10 @@ impl<T> ResponseHandle<'_, T, DeleteParamsWebhook> {
     }
 }
 
-fn spawn_delete(http: Arc<Client>, params: Params, delete_after: Duration) {
-    tokio::spawn(async move {
-        sleep(delete_after).await;
+fn spawn_delete(bot: &Bot, params: Params, delete_after: Duration) {
+    let http = Arc::clone(&bot.http);
+
+    bot.tasks.spawn_after(delete_after, async move {
         let _delete_res = match params {
             Params::Message(channel_id, message_id) => {
                 http.delete_message(channel_id, message_id).await
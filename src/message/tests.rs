@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+
+use twilight_model::{guild::Permissions, id::Id};
+
+use crate::{
+    embed::EmbedBuilder,
+    error::Error,
+    message::missing_permissions,
+    permissions::PermissionCalculator,
+    reply::Reply,
+};
+
+fn calculator(role_permissions: Permissions) -> PermissionCalculator {
+    PermissionCalculator::new(
+        Id::new(1),
+        Id::new(2),
+        Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES,
+        vec![(Id::new(3), role_permissions)],
+    )
+}
+
+#[test]
+fn missing_permissions_in_channel() {
+    let reply = Reply::new()
+        .content("hi")
+        .embed(EmbedBuilder::new().description("embed").build().unwrap());
+
+    assert!(matches!(
+        missing_permissions(
+            &reply,
+            calculator(Permissions::empty()).in_channel(&[]),
+            &HashSet::new(),
+            false,
+        ),
+        Err(Error::MissingPermissions(Permissions::EMBED_LINKS))
+    ));
+    missing_permissions(
+        &reply,
+        calculator(Permissions::EMBED_LINKS).in_channel(&[]),
+        &HashSet::new(),
+        false,
+    )
+    .unwrap();
+}
+
+#[test]
+fn missing_permissions_in_thread() {
+    let reply = Reply::new().content("hi").sticker(Id::new(4));
+
+    assert!(matches!(
+        missing_permissions(
+            &reply,
+            calculator(Permissions::empty()).in_thread(&[]),
+            &HashSet::from([Id::new(4)]),
+            true,
+        ),
+        Err(Error::MissingPermissions(
+            Permissions::SEND_MESSAGES_IN_THREADS
+        ))
+    ));
+    assert!(matches!(
+        missing_permissions(
+            &reply,
+            calculator(Permissions::SEND_MESSAGES_IN_THREADS).in_thread(&[]),
+            &HashSet::new(),
+            true,
+        ),
+        Err(Error::MissingPermissions(
+            Permissions::USE_EXTERNAL_STICKERS
+        ))
+    ));
+}
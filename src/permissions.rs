@@ -0,0 +1,406 @@
+//! Calculating the permissions of members without relying on Discord
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::HashSet,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use twilight_model::{
+    channel::{
+        Channel,
+        permission_overwrite::{PermissionOverwrite, PermissionOverwriteType},
+    },
+    guild::{Guild, Member, Permissions},
+    id::{
+        Id,
+        marker::{ChannelMarker, GuildMarker, RoleMarker, StickerMarker, UserMarker},
+    },
+    util::Timestamp,
+};
+
+use crate::{
+    Bot,
+    error::{Error, UserError},
+};
+
+/// Permissions that are removed from a member without
+/// [`Permissions::SEND_MESSAGES`] in a channel
+const SEND_MESSAGES_DEPENDENT: Permissions = Permissions::MENTION_EVERYONE
+    .union(Permissions::SEND_TTS_MESSAGES)
+    .union(Permissions::ATTACH_FILES)
+    .union(Permissions::EMBED_LINKS);
+
+/// Permissions a timed out member keeps
+const TIMED_OUT_ALLOWED: Permissions =
+    Permissions::VIEW_CHANNEL.union(Permissions::READ_MESSAGE_HISTORY);
+
+/// Calculates the permissions of a member from the guild's roles and the
+/// channel's permission overwrites
+///
+/// Create this with [`PermissionCalculator::new`] or
+/// [`PermissionCalculator::from_guild`] for fetched data, or with
+/// [`Bot::permission_calculator`] to use the cache when possible
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermissionCalculator {
+    everyone_permissions: Permissions,
+    guild_id: Id<GuildMarker>,
+    member_roles: Vec<(Id<RoleMarker>, Permissions)>,
+    owner: bool,
+    timed_out: bool,
+    user_id: Id<UserMarker>,
+}
+
+impl PermissionCalculator {
+    /// Set when the member's timeout ends, a timed out member only has
+    /// [`Permissions::VIEW_CHANNEL`] and [`Permissions::READ_MESSAGE_HISTORY`]
+    /// unless they're an administrator or the owner
+    #[must_use]
+    pub fn communication_disabled_until(mut self, until: Option<Timestamp>) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| {
+                i64::try_from(since_epoch.as_micros()).unwrap_or(i64::MAX)
+            });
+
+        self.timed_out = until.is_some_and(|timestamp| timestamp.as_micros() > now);
+        self
+    }
+
+    /// Create a calculator from a fetched guild and member
+    #[must_use]
+    pub fn from_guild(guild: &Guild, member: &Member) -> Self {
+        Self::new(
+            guild.id,
+            member.user.id,
+            role_permissions(guild, guild.id.cast()),
+            member
+                .roles
+                .iter()
+                .map(|role_id| (*role_id, role_permissions(guild, *role_id)))
+                .collect(),
+        )
+        .owner(guild.owner_id == member.user.id)
+        .communication_disabled_until(member.communication_disabled_until)
+    }
+
+    /// Return the member's permissions in a channel with the given permission
+    /// overwrites
+    ///
+    /// Members without [`Permissions::VIEW_CHANNEL`] don't have any
+    /// permissions in the channel, members without
+    /// [`Permissions::SEND_MESSAGES`] also don't have the permissions that
+    /// depend on it, such as [`Permissions::EMBED_LINKS`]
+    #[must_use]
+    pub fn in_channel(&self, overwrites: &[PermissionOverwrite]) -> Permissions {
+        let root = self.root();
+        if root.contains(Permissions::ADMINISTRATOR) {
+            return Permissions::all();
+        }
+
+        self.restrict(self.overwritten(root, overwrites))
+    }
+
+    /// Return the member's permissions in a thread whose parent channel has
+    /// the given permission overwrites
+    ///
+    /// Threads inherit the permission overwrites of their parent channel,
+    /// [`Permissions::SEND_MESSAGES`] is set based on
+    /// [`Permissions::SEND_MESSAGES_IN_THREADS`] since that's what's required
+    /// to send messages in threads, the permissions that depend on it are
+    /// then removed the same way as in [`PermissionCalculator::in_channel`]
+    #[must_use]
+    pub fn in_thread(&self, parent_overwrites: &[PermissionOverwrite]) -> Permissions {
+        let root = self.root();
+        if root.contains(Permissions::ADMINISTRATOR) {
+            return Permissions::all();
+        }
+
+        let mut permissions = self.overwritten(root, parent_overwrites);
+        if permissions.contains(Permissions::SEND_MESSAGES_IN_THREADS) {
+            permissions.insert(Permissions::SEND_MESSAGES);
+        } else {
+            permissions.remove(Permissions::SEND_MESSAGES);
+        }
+
+        self.restrict(permissions)
+    }
+
+    /// Create a calculator from the guild's `@everyone` role permissions and
+    /// the IDs and permissions of the member's roles
+    #[must_use]
+    pub const fn new(
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        everyone_permissions: Permissions,
+        member_roles: Vec<(Id<RoleMarker>, Permissions)>,
+    ) -> Self {
+        Self {
+            everyone_permissions,
+            guild_id,
+            member_roles,
+            owner: false,
+            timed_out: false,
+            user_id,
+        }
+    }
+
+    /// Apply the permission overwrites to the member's root permissions,
+    /// returning no permissions without [`Permissions::VIEW_CHANNEL`]
+    fn overwritten(&self, root: Permissions, overwrites: &[PermissionOverwrite]) -> Permissions {
+        let mut permissions = root;
+
+        if let Some(everyone_overwrite) = overwrites
+            .iter()
+            .find(|overwrite| overwrite.id == self.guild_id.cast())
+        {
+            permissions.remove(everyone_overwrite.deny);
+            permissions.insert(everyone_overwrite.allow);
+        }
+
+        let (role_allow, role_deny) = overwrites
+            .iter()
+            .filter(|overwrite| {
+                overwrite.kind == PermissionOverwriteType::Role
+                    && self
+                        .member_roles
+                        .iter()
+                        .any(|(role_id, _)| overwrite.id == role_id.cast())
+            })
+            .fold(
+                (Permissions::empty(), Permissions::empty()),
+                |(allow, deny), overwrite| (allow | overwrite.allow, deny | overwrite.deny),
+            );
+        permissions.remove(role_deny);
+        permissions.insert(role_allow);
+
+        if let Some(member_overwrite) = overwrites.iter().find(|overwrite| {
+            overwrite.kind == PermissionOverwriteType::Member && overwrite.id == self.user_id.cast()
+        }) {
+            permissions.remove(member_overwrite.deny);
+            permissions.insert(member_overwrite.allow);
+        }
+
+        if !permissions.contains(Permissions::VIEW_CHANNEL) {
+            return Permissions::empty();
+        }
+
+        permissions
+    }
+
+    /// Set whether the member is the owner of the guild, the owner has all
+    /// permissions
+    #[must_use]
+    pub const fn owner(mut self, owner: bool) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    /// Remove the permissions that depend on [`Permissions::SEND_MESSAGES`] if
+    /// it's missing and the ones a timed out member doesn't have
+    fn restrict(&self, mut permissions: Permissions) -> Permissions {
+        if !permissions.contains(Permissions::SEND_MESSAGES) {
+            permissions.remove(SEND_MESSAGES_DEPENDENT);
+        }
+        if self.timed_out {
+            permissions &= TIMED_OUT_ALLOWED;
+        }
+
+        permissions
+    }
+
+    /// Return the member's permissions in the guild, without any channel's
+    /// permission overwrites
+    #[must_use]
+    pub fn root(&self) -> Permissions {
+        if self.owner {
+            return Permissions::all();
+        }
+
+        let permissions = self
+            .member_roles
+            .iter()
+            .fold(self.everyone_permissions, |permissions, (_, role)| {
+                permissions | *role
+            });
+
+        if permissions.contains(Permissions::ADMINISTRATOR) {
+            return Permissions::all();
+        }
+        if self.timed_out {
+            return permissions & TIMED_OUT_ALLOWED;
+        }
+
+        permissions
+    }
+}
+
+impl Bot {
+    #[cfg(feature = "cache")]
+    fn cached_permission_calculator(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+    ) -> Option<PermissionCalculator> {
+        let owner_id = self.cache.guild(guild_id)?.owner_id();
+        let member = self.cache.member(guild_id, user_id)?;
+        let everyone_permissions = self.cache.role(guild_id.cast())?.permissions;
+
+        let member_roles = member
+            .roles()
+            .iter()
+            .map(|role_id| {
+                self.cache
+                    .role(*role_id)
+                    .map(|role| (*role_id, role.permissions))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(
+            PermissionCalculator::new(guild_id, user_id, everyone_permissions, member_roles)
+                .owner(owner_id == user_id)
+                .communication_disabled_until(member.communication_disabled_until()),
+        )
+    }
+
+    pub(crate) async fn channel(&self, channel_id: Id<ChannelMarker>) -> Result<Channel, Error> {
+        #[cfg(feature = "cache")]
+        if let Some(channel) = self.cache.channel(channel_id) {
+            return Ok(channel.clone());
+        }
+
+        Ok(self.http.channel(channel_id).await?.model().await?)
+    }
+
+    pub(crate) async fn channel_permissions(
+        &self,
+        channel: &Channel,
+        user_id: Id<UserMarker>,
+    ) -> Result<Permissions, Error> {
+        let Some(guild_id) = channel.guild_id else {
+            return Ok(Permissions::all());
+        };
+
+        let calculator = self.permission_calculator(guild_id, user_id).await?;
+
+        match channel.parent_id {
+            Some(parent_id) if channel.kind.is_thread() => {
+                let parent = self.channel(parent_id).await?;
+                Ok(calculator
+                    .in_thread(parent.permission_overwrites.as_deref().unwrap_or_default()))
+            }
+            _ => {
+                Ok(calculator
+                    .in_channel(channel.permission_overwrites.as_deref().unwrap_or_default()))
+            }
+        }
+    }
+
+    /// Check that the bot has the required permissions in the given channel,
+    /// useful for message-based commands where Discord doesn't send the bot's
+    /// permissions
+    ///
+    /// See [`Bot::permissions_in`] for how the permissions are calculated
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UserError::MissingPermissions`] with the missing permissions
+    /// if the bot doesn't have all the required permissions
+    ///
+    /// Returns [`UserError::Internal`] if getting the permissions fails
+    pub async fn check_permissions<C>(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        required: Permissions,
+    ) -> Result<(), UserError<C>> {
+        let permissions_res = self.permissions_in(channel_id, self.user_id).await;
+        let permissions = permissions_res.map_err(|err| UserError::from_err(&err))?;
+
+        let missing = required.difference(permissions);
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(UserError::MissingPermissions(Some(missing)))
+        }
+    }
+
+    pub(crate) async fn guild_sticker_ids(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<HashSet<Id<StickerMarker>>, Error> {
+        #[cfg(feature = "cache")]
+        if let Some(sticker_ids) = self.cache.guild_stickers(guild_id) {
+            return Ok(sticker_ids.clone());
+        }
+
+        Ok(self
+            .http
+            .guild_stickers(guild_id)
+            .await?
+            .models()
+            .await?
+            .into_iter()
+            .map(|sticker| sticker.id)
+            .collect())
+    }
+
+    /// Return a permission calculator for the given member, using the cache
+    /// if the `cache` feature is enabled and the guild, its roles and the
+    /// member are cached, fetching them otherwise
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if fetching the
+    /// guild or member fails
+    pub async fn permission_calculator(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+    ) -> Result<PermissionCalculator, Error> {
+        #[cfg(feature = "cache")]
+        if let Some(calculator) = self.cached_permission_calculator(guild_id, user_id) {
+            return Ok(calculator);
+        }
+
+        let guild = self.http.guild(guild_id).await?.model().await?;
+        let member = self
+            .http
+            .guild_member(guild_id, user_id)
+            .await?
+            .model()
+            .await?;
+
+        Ok(PermissionCalculator::from_guild(&guild, &member))
+    }
+
+    /// Return the permissions of the given user in the given channel, using
+    /// the cache if the `cache` feature is enabled and the data is cached,
+    /// fetching it otherwise
+    ///
+    /// Permission overwrites of thread channels are inherited from their
+    /// parent channel, channels outside of guilds have all permissions
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if fetching the
+    /// data fails
+    pub async fn permissions_in(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        user_id: Id<UserMarker>,
+    ) -> Result<Permissions, Error> {
+        let channel = self.channel(channel_id).await?;
+
+        self.channel_permissions(&channel, user_id).await
+    }
+}
+
+fn role_permissions(guild: &Guild, role_id: Id<RoleMarker>) -> Permissions {
+    guild
+        .roles
+        .iter()
+        .find(|role| role.id == role_id)
+        .map_or(Permissions::empty(), |role| role.permissions)
+}
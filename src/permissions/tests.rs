@@ -0,0 +1,136 @@
+use twilight_model::{
+    channel::permission_overwrite::{PermissionOverwrite, PermissionOverwriteType},
+    guild::Permissions,
+    id::Id,
+    util::Timestamp,
+};
+
+use crate::permissions::PermissionCalculator;
+
+fn calculator() -> PermissionCalculator {
+    PermissionCalculator::new(
+        Id::new(1),
+        Id::new(2),
+        Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES,
+        vec![(Id::new(3), Permissions::EMBED_LINKS)],
+    )
+}
+
+fn overwrite(
+    id: u64,
+    kind: PermissionOverwriteType,
+    allow: Permissions,
+    deny: Permissions,
+) -> PermissionOverwrite {
+    PermissionOverwrite {
+        allow,
+        deny,
+        id: Id::new(id),
+        kind,
+    }
+}
+
+#[test]
+fn permissions_root() {
+    assert_eq!(
+        calculator().root(),
+        Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS
+    );
+    assert_eq!(calculator().owner(true).root(), Permissions::all());
+    assert_eq!(
+        PermissionCalculator::new(Id::new(1), Id::new(2), Permissions::ADMINISTRATOR, vec![])
+            .root(),
+        Permissions::all()
+    );
+}
+
+#[test]
+fn permissions_overwrites() {
+    let overwrites = [
+        overwrite(
+            1,
+            PermissionOverwriteType::Role,
+            Permissions::ATTACH_FILES,
+            Permissions::SEND_MESSAGES,
+        ),
+        overwrite(
+            3,
+            PermissionOverwriteType::Role,
+            Permissions::SEND_MESSAGES,
+            Permissions::empty(),
+        ),
+        overwrite(
+            2,
+            PermissionOverwriteType::Member,
+            Permissions::empty(),
+            Permissions::EMBED_LINKS,
+        ),
+    ];
+
+    assert_eq!(
+        calculator().in_channel(&overwrites),
+        Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::ATTACH_FILES
+    );
+    assert_eq!(
+        calculator().in_channel(&overwrites[..1]),
+        Permissions::VIEW_CHANNEL
+    );
+    assert_eq!(
+        calculator().in_channel(&[overwrite(
+            1,
+            PermissionOverwriteType::Role,
+            Permissions::empty(),
+            Permissions::VIEW_CHANNEL,
+        )]),
+        Permissions::empty()
+    );
+}
+
+#[test]
+fn permissions_thread() {
+    assert!(
+        !calculator()
+            .in_thread(&[])
+            .contains(Permissions::SEND_MESSAGES)
+    );
+    assert!(
+        calculator()
+            .in_thread(&[overwrite(
+                1,
+                PermissionOverwriteType::Role,
+                Permissions::SEND_MESSAGES_IN_THREADS,
+                Permissions::empty(),
+            )])
+            .contains(Permissions::SEND_MESSAGES)
+    );
+}
+
+#[test]
+fn permissions_thread_send_messages_dependent() {
+    let thread_only = overwrite(
+        1,
+        PermissionOverwriteType::Role,
+        Permissions::SEND_MESSAGES_IN_THREADS,
+        Permissions::SEND_MESSAGES,
+    );
+    assert_eq!(
+        calculator().in_thread(&[thread_only]),
+        Permissions::VIEW_CHANNEL
+            | Permissions::SEND_MESSAGES
+            | Permissions::SEND_MESSAGES_IN_THREADS
+            | Permissions::EMBED_LINKS
+    );
+
+    assert_eq!(calculator().in_thread(&[]), Permissions::VIEW_CHANNEL);
+}
+
+#[test]
+fn permissions_timed_out() {
+    let timed_out = calculator()
+        .communication_disabled_until(Some(Timestamp::from_secs(i64::from(i32::MAX)).unwrap()));
+    assert_eq!(timed_out.in_channel(&[]), Permissions::VIEW_CHANNEL);
+
+    let timeout_ended =
+        calculator().communication_disabled_until(Some(Timestamp::from_secs(1).unwrap()));
+    assert_eq!(timeout_ended.root(), calculator().root());
+}
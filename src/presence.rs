@@ -0,0 +1,190 @@
+//! Setting the bot's status and activities and rotating through them
+
+#[cfg(test)]
+mod tests;
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::time::timeout;
+use twilight_gateway::ShardId;
+use twilight_model::gateway::{
+    OpCode,
+    payload::outgoing::{UpdatePresence, update_presence::UpdatePresencePayload},
+    presence::{Activity, ActivityType, MinimalActivity, Status},
+};
+
+use crate::{Bot, Shards, error::Error, receiver::ShardSender};
+
+/// A status and activity whose name can have placeholders, used in
+/// [`Bot::spawn_presence_rotation`]
+///
+/// The placeholders are:
+/// - `{guild_count}`: The number of guilds on all shards, see
+///   [`ShardTracker::guild_count`]
+/// - `{shard_count}`: The total number of shards
+/// - `{shard_id}`: The ID of the shard the presence is set on
+///
+/// [`ShardTracker::guild_count`]: crate::status::ShardTracker::guild_count
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PresenceTemplate {
+    /// The type of the activity
+    pub kind: ActivityType,
+    /// The name of the activity, with placeholders
+    pub name: String,
+    /// The status to set with the activity
+    pub status: Status,
+}
+
+impl PresenceTemplate {
+    /// Create a new template with the online status
+    #[must_use]
+    pub fn new<T: Into<String>>(kind: ActivityType, name: T) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            status: Status::Online,
+        }
+    }
+
+    #[expect(
+        clippy::literal_string_with_formatting_args,
+        reason = "these are placeholders in the template"
+    )]
+    fn render(&self, guild_count: usize, shard_id: ShardId) -> UpdatePresence {
+        presence(
+            self.status,
+            vec![activity(
+                self.kind,
+                self.name
+                    .replace("{guild_count}", &guild_count.to_string())
+                    .replace("{shard_count}", &shard_id.total().to_string())
+                    .replace("{shard_id}", &shard_id.number().to_string()),
+            )],
+        )
+    }
+
+    /// Set the status to set with the activity
+    #[must_use]
+    pub const fn status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl Bot {
+    /// Spawn a task that sets the presence of all shards to the next template
+    /// every `interval`, starting from the first one
+    ///
+    /// The guild count is read from [`Bot::shard_tracker`], so it must be
+    /// updated for it to be accurate
+    ///
+    /// The task stops when shutting down or if `templates` is empty
+    pub fn spawn_presence_rotation(
+        self: Arc<Self>,
+        sender: ShardSender,
+        templates: Vec<PresenceTemplate>,
+        interval: Duration,
+    ) {
+        let tasks = self.tasks.clone();
+
+        tasks.spawn_internal(async move {
+            for template in templates.iter().cycle() {
+                let guild_count = self.shard_tracker.guild_count();
+                for shard_id in sender.shard_ids() {
+                    let _command_res =
+                        sender.command(shard_id, &template.render(guild_count, shard_id));
+                }
+
+                let shutdown_res = timeout(interval, self.tasks.shutting_down()).await;
+                if shutdown_res.is_ok() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[expect(
+    clippy::result_large_err,
+    reason = "the error type is shared with the rest of the library"
+)]
+impl ShardSender {
+    /// Set the status and activities of all shards
+    ///
+    /// Use [`activity`] to create an activity from its type and name
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors [`ShardSender::command_all`] returns
+    pub fn set_presence(&self, status: Status, activities: Vec<Activity>) -> Result<(), Error> {
+        self.command_all(&presence(status, activities))
+    }
+
+    /// Set the status and activities of the shard with the given ID
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors [`ShardSender::command`] returns
+    pub fn set_shard_presence(
+        &self,
+        shard_id: ShardId,
+        status: Status,
+        activities: Vec<Activity>,
+    ) -> Result<(), Error> {
+        self.command(shard_id, &presence(status, activities))
+    }
+}
+
+impl Shards {
+    /// Set the status and activities of all shards
+    ///
+    /// Use [`ShardSender::set_presence`] instead after calling
+    /// [`Shards::into_receiver`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Send`] if sending the command to a shard fails, the
+    /// presence is still set on the other shards
+    pub async fn set_presence(
+        &mut self,
+        status: Status,
+        activities: Vec<Activity>,
+    ) -> Result<(), Error> {
+        let command = presence(status, activities);
+        let mut send_res = Ok(());
+
+        for shard in &mut self.0 {
+            let shard_send_res = shard.command(&command).await;
+            if let Err(err) = shard_send_res {
+                send_res = Err(err.into());
+            }
+        }
+
+        send_res
+    }
+}
+
+/// Create an activity with the given type and name
+#[must_use]
+pub fn activity<T: Into<String>>(kind: ActivityType, name: T) -> Activity {
+    MinimalActivity {
+        kind,
+        name: name.into(),
+        url: None,
+    }
+    .into()
+}
+
+/// Unlike [`UpdatePresence::new`], this allows setting only the status without
+/// any activities
+const fn presence(status: Status, activities: Vec<Activity>) -> UpdatePresence {
+    UpdatePresence {
+        d: UpdatePresencePayload {
+            activities,
+            afk: false,
+            since: None,
+            status,
+        },
+        op: OpCode::PresenceUpdate,
+    }
+}
@@ -0,0 +1,21 @@
+use twilight_gateway::ShardId;
+use twilight_model::gateway::presence::{ActivityType, Status};
+
+use crate::presence::PresenceTemplate;
+
+#[test]
+fn presence_template_render() {
+    let presence = PresenceTemplate::new(
+        ActivityType::Watching,
+        "{guild_count} servers on shard {shard_id}/{shard_count}",
+    )
+    .status(Status::Idle)
+    .render(42, ShardId::new(1, 4));
+
+    assert_eq!(presence.d.status, Status::Idle);
+    assert_eq!(presence.d.activities.len(), 1);
+
+    let activity = presence.d.activities.first().unwrap();
+    assert_eq!(activity.kind, ActivityType::Watching);
+    assert_eq!(activity.name, "42 servers on shard 1/4");
+}
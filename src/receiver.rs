@@ -0,0 +1,266 @@
+//! Receiving events from the shards without borrowing them
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "cache")]
+use std::sync::Arc;
+use std::{collections::HashMap, pin::pin};
+
+use futures_util::future::{Either, select};
+use tokio::{
+    sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    task::JoinHandle,
+};
+#[cfg(feature = "cache")]
+use twilight_cache_inmemory::InMemoryCache;
+use twilight_gateway::{Command, MessageSender, Shard, ShardId};
+use twilight_model::{
+    gateway::{event::Event, payload::outgoing::RequestGuildMembers},
+    id::{Id, marker::GuildMarker},
+};
+
+use crate::{
+    Bot,
+    Shards,
+    error::Error,
+    shutdown::{ShutdownOptions, Tasks},
+    status::ShardTracker,
+};
+
+/// Receives the events of all the shards, returned in
+/// [`Shards::into_receiver`]
+///
+/// Each shard is run in its own task, so this is `'static` and can be moved
+/// to other tasks
+///
+/// The events are buffered in an unbounded channel, since a shard that waits
+/// for the receiver can't respond to heartbeats and would be disconnected, so
+/// events should be received without long pauses, for example by handling
+/// them in their own tasks like [`EventReceiver::dispatch`] does, otherwise
+/// the buffer keeps growing
+#[derive(Debug)]
+pub struct EventReceiver {
+    events: UnboundedReceiver<Result<(ShardId, Event), Error>>,
+    shards: Vec<JoinHandle<Shard>>,
+}
+
+impl EventReceiver {
+    /// Receive the next event and the ID of the shard that received it
+    ///
+    /// Returns `None` after all shards stopped, which happens when shutting
+    /// down or after every shard was fatally closed
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ShardFatal`] when a shard is fatally closed, for
+    /// example because of an invalid token or intents, the shard isn't
+    /// restarted but the other shards keep running
+    pub async fn recv(&mut self) -> Option<Result<(ShardId, Event), Error>> {
+        self.events.recv().await
+    }
+
+    /// Stop the shards' tasks and shut down the bot gracefully
+    ///
+    /// See [`Shards::shutdown`] for what this does
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors [`Shards::shutdown`] returns
+    pub async fn shutdown(self, bot: &Bot, options: ShutdownOptions<'_>) -> Result<(), Error> {
+        bot.tasks.start_shutdown();
+
+        let mut shards = Vec::with_capacity(self.shards.len());
+        for handle in self.shards {
+            let join_res = handle.await;
+            if let Ok(shard) = join_res {
+                shards.push(shard);
+            }
+        }
+
+        Shards(shards).shutdown(bot, options).await
+    }
+}
+
+/// Sends gateway commands to the shards, returned in [`Shards::into_receiver`]
+///
+/// This is cheap to clone and can be used while receiving events
+#[derive(Clone, Debug)]
+pub struct ShardSender {
+    senders: HashMap<ShardId, MessageSender>,
+}
+
+#[expect(
+    clippy::result_large_err,
+    reason = "the error type is shared with the rest of the library"
+)]
+impl ShardSender {
+    /// Send the given command to the shard with the given ID
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownShard`] if the shard isn't run by this process
+    ///
+    /// Returns [`Error::Send`] if the shard's task has stopped
+    pub fn command<T: Command>(&self, shard_id: ShardId, command: &T) -> Result<(), Error> {
+        self.senders
+            .get(&shard_id)
+            .ok_or(Error::UnknownShard(shard_id))?
+            .command(command)?;
+
+        Ok(())
+    }
+
+    /// Send the given command to all the shards, for example to update the
+    /// presence
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Send`] if a shard's task has stopped, the command is
+    /// still sent to the other shards
+    pub fn command_all<T: Command>(&self, command: &T) -> Result<(), Error> {
+        let send_err = self
+            .senders
+            .values()
+            .filter_map(|sender| sender.command(command).err())
+            .last();
+
+        send_err.map_or(Ok(()), |err| Err(err.into()))
+    }
+
+    /// Request the members of the guild the command is for from the shard
+    /// that guild is on
+    ///
+    /// The members are received in member chunk events
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors [`ShardSender::command`] returns
+    pub fn request_guild_members(&self, command: &RequestGuildMembers) -> Result<(), Error> {
+        self.command(self.shard_for_guild(command.d.guild_id)?, command)
+    }
+
+    /// Return the ID of the shard the given guild is on
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownShard`] if there are no shards
+    pub fn shard_for_guild(&self, guild_id: Id<GuildMarker>) -> Result<ShardId, Error> {
+        let total = self
+            .senders
+            .keys()
+            .next()
+            .map_or(1, |shard_id| shard_id.total());
+
+        let shard_id = guild_shard_id(guild_id, total);
+
+        if self.senders.contains_key(&shard_id) {
+            Ok(shard_id)
+        } else {
+            Err(Error::UnknownShard(shard_id))
+        }
+    }
+
+    /// Return the IDs of the shards, sorted by their numbers
+    #[must_use]
+    pub fn shard_ids(&self) -> Vec<ShardId> {
+        let mut shard_ids = self.senders.keys().copied().collect::<Vec<_>>();
+        shard_ids.sort_by_key(|shard_id| shard_id.number());
+
+        shard_ids
+    }
+}
+
+impl Shards {
+    /// Move each shard into its own task, returning a receiver for their
+    /// events and a sender for gateway commands
+    ///
+    /// Unlike [`Shards::events`], the receiver and sender don't borrow the
+    /// shards, so commands can be sent while receiving events and the
+    /// receiver can be moved to another task
+    ///
+    /// [`Bot::shard_tracker`] and the cache are updated with every event, shut
+    /// down with [`EventReceiver::shutdown`] to save the shards' sessions
+    #[must_use]
+    pub fn into_receiver(self, bot: &Bot) -> (EventReceiver, ShardSender) {
+        let (event_sender, events) = unbounded_channel();
+
+        let senders = self
+            .0
+            .iter()
+            .map(|shard| (shard.id(), shard.sender()))
+            .collect();
+
+        let shards = self
+            .0
+            .into_iter()
+            .map(|shard| {
+                tokio::spawn(run_shard(
+                    shard,
+                    bot.tasks.clone(),
+                    bot.shard_tracker.clone(),
+                    #[cfg(feature = "cache")]
+                    Arc::clone(&bot.cache),
+                    event_sender.clone(),
+                ))
+            })
+            .collect();
+
+        (EventReceiver { events, shards }, ShardSender { senders })
+    }
+}
+
+async fn run_shard(
+    mut shard: Shard,
+    tasks: Tasks,
+    shard_tracker: ShardTracker,
+    #[cfg(feature = "cache")] cache: Arc<InMemoryCache>,
+    events: UnboundedSender<Result<(ShardId, Event), Error>>,
+) -> Shard {
+    loop {
+        let event_res = {
+            let next_event = pin!(shard.next_event());
+            let shutdown = pin!(tasks.shutting_down());
+            let next = select(next_event, shutdown).await;
+            match next {
+                Either::Left((event_res, _)) => Some(event_res),
+                Either::Right(_) => None,
+            }
+        };
+        shard_tracker.update(&shard);
+
+        match event_res {
+            Some(Ok(event)) => {
+                shard_tracker.track_event(shard.id(), &event);
+                #[cfg(feature = "cache")]
+                cache.update(&event);
+                if events.send(Ok((shard.id(), event))).is_err() {
+                    break;
+                }
+            }
+            Some(Err(err)) if err.is_fatal() => {
+                let _send_res = events.send(Err(Error::ShardFatal {
+                    shard_id: shard.id(),
+                    source: err,
+                }));
+                break;
+            }
+            Some(Err(_)) => {}
+            None => break,
+        }
+    }
+
+    shard
+}
+
+fn guild_shard_id(guild_id: Id<GuildMarker>, total: u64) -> ShardId {
+    ShardId::new(
+        guild_id
+            .get()
+            .checked_shr(22)
+            .unwrap_or(0)
+            .checked_rem(total)
+            .unwrap_or(0),
+        total,
+    )
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use futures_util::StreamExt as _;
+use tokio::{net::TcpListener, runtime::Builder};
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, frame::coding::CloseCode};
+use twilight_gateway::{Intents, Shard, ShardId};
+use twilight_model::{gateway::payload::outgoing::RequestGuildMembers, id::Id};
+
+use crate::{
+    builder::{BotBuilder, ShardSelection},
+    error::Error,
+    receiver::{ShardSender, guild_shard_id},
+};
+
+const TOKEN: &str = "MTIzNDU2Nzg5MDEyMzQ1Njc4.GabcDe.secret";
+
+fn request_guild_members() -> RequestGuildMembers {
+    RequestGuildMembers::builder(Id::new(1)).query("", None)
+}
+
+fn shards(shard_ids: &[ShardId]) -> Vec<Shard> {
+    shard_ids
+        .iter()
+        .map(|shard_id| Shard::new(*shard_id, TOKEN.to_owned(), Intents::empty()))
+        .collect()
+}
+
+fn sender(shards: &[Shard]) -> ShardSender {
+    ShardSender {
+        senders: shards
+            .iter()
+            .map(|shard| (shard.id(), shard.sender()))
+            .collect::<HashMap<_, _>>(),
+    }
+}
+
+#[test]
+fn guild_shard_id_from_timestamp() {
+    assert_eq!(guild_shard_id(Id::new(1 << 22), 2), ShardId::new(1, 2));
+    assert_eq!(guild_shard_id(Id::new(2 << 22), 2), ShardId::new(0, 2));
+    assert_eq!(
+        guild_shard_id(Id::new(81_384_788_765_712_384), 16),
+        ShardId::new(2, 16)
+    );
+    assert_eq!(guild_shard_id(Id::new(1), 1), ShardId::ONE);
+}
+
+#[test]
+fn shard_sender_command() {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let managed = shards(&[ShardId::new(0, 2)]);
+            let shard_sender = sender(&managed);
+
+            shard_sender
+                .command(ShardId::new(0, 2), &request_guild_members())
+                .unwrap();
+            assert!(matches!(
+                shard_sender.command(ShardId::new(1, 2), &request_guild_members()),
+                Err(Error::UnknownShard(shard_id)) if shard_id == ShardId::new(1, 2)
+            ));
+        });
+}
+
+#[test]
+fn shard_sender_command_all() {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let mut managed = shards(&[ShardId::new(0, 2), ShardId::new(1, 2)]);
+            let shard_sender = sender(&managed);
+
+            shard_sender.command_all(&request_guild_members()).unwrap();
+
+            managed.pop();
+            assert!(matches!(
+                shard_sender.command_all(&request_guild_members()),
+                Err(Error::Send(_))
+            ));
+            shard_sender
+                .command(ShardId::new(0, 2), &request_guild_members())
+                .unwrap();
+        });
+}
+
+#[test]
+fn shard_sender_shard_for_guild() {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let managed = shards(&[ShardId::new(0, 2)]);
+            let shard_sender = sender(&managed);
+
+            assert_eq!(
+                shard_sender.shard_for_guild(Id::new(2 << 22)).unwrap(),
+                ShardId::new(0, 2)
+            );
+            assert!(matches!(
+                shard_sender.shard_for_guild(Id::new(1 << 22)),
+                Err(Error::UnknownShard(shard_id)) if shard_id == ShardId::new(1, 2)
+            ));
+            assert!(matches!(
+                sender(&[]).shard_for_guild(Id::new(1 << 22)),
+                Err(Error::UnknownShard(_))
+            ));
+        });
+}
+
+#[test]
+fn event_receiver_shard_fatal() {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let gateway_url = format!("ws://{}", listener.local_addr().unwrap());
+
+            let gateway = tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut websocket = tokio_tungstenite::accept_async(stream).await.unwrap();
+                websocket
+                    .close(Some(CloseFrame {
+                        code: CloseCode::Library(4004),
+                        reason: "Authentication failed.".into(),
+                    }))
+                    .await
+                    .unwrap();
+                while websocket.next().await.is_some() {}
+            });
+
+            let (bot, bot_shards) = BotBuilder::new(TOKEN, Intents::empty())
+                .fetch_application(false)
+                .fetch_user(false)
+                .shards(ShardSelection::Total(1))
+                .gateway_config(|config| config.proxy_url(gateway_url))
+                .build()
+                .await
+                .unwrap();
+            let (mut events, _) = bot_shards.into_receiver(&bot);
+
+            let mut fatal_shard_id = None;
+            loop {
+                let Some(event_res) = events.recv().await else {
+                    break;
+                };
+                if let Err(Error::ShardFatal { shard_id, source }) = event_res {
+                    assert!(source.is_fatal());
+                    fatal_shard_id = Some(shard_id);
+                }
+            }
+
+            assert_eq!(fatal_shard_id, Some(ShardId::ONE));
+            gateway.await.unwrap();
+        });
+}
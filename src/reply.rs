@@ -1,14 +1,33 @@
 //! The [`Reply`] struct combining data to use when creating a message,
 //! interaction response or executing a webhook
 
+pub(crate) mod split;
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
+
 use twilight_model::{
-    channel::message::{AllowedMentions, Component, Embed, MessageFlags},
+    channel::message::{AllowedMentions, Component, Embed, MentionType, MessageFlags},
+    guild::Permissions,
     http::{attachment::Attachment, interaction::InteractionResponseData},
     id::{
         Id,
         marker::{ChannelMarker, MessageMarker, StickerMarker},
     },
 };
+use twilight_validate::{
+    component::{self, COMPONENT_COUNT},
+    embed::{self, EMBED_TOTAL_LENGTH},
+    message::{self, EMBED_COUNT_LIMIT, MESSAGE_CONTENT_LENGTH_MAX},
+    request,
+};
+
+use crate::{
+    component::{ButtonBuilder, SelectMenuBuilder, push_to_rows},
+    embed::EmbedBuilder,
+    error::{ReplyValidationError, ValidationReport},
+};
 
 impl From<Reply> for InteractionResponseData {
     fn from(reply: Reply) -> Self {
@@ -27,6 +46,52 @@ impl From<Reply> for InteractionResponseData {
     }
 }
 
+/// The context a [`Reply`] is sent in, used in [`Reply::required_permissions`]
+///
+/// The default context is a channel that isn't a thread, with no default
+/// allowed mentions and no known guild stickers, and doesn't include
+/// [`Permissions::MENTION_EVERYONE`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PermissionContext<'context> {
+    /// The bot's default allowed mentions, used if the reply doesn't set its
+    /// own
+    ///
+    /// If neither is set, all mentions in the content are parsed
+    pub default_allowed_mentions: Option<&'context AllowedMentions>,
+    /// The IDs of the stickers of the guild the reply is sent in
+    ///
+    /// Stickers not in this are external, if this is `None`, all stickers are
+    /// assumed to be external
+    pub guild_sticker_ids: Option<&'context HashSet<Id<StickerMarker>>>,
+    /// Whether to include [`Permissions::MENTION_EVERYONE`] if the content
+    /// mentions `@everyone` or `@here`
+    ///
+    /// Without the permission, the message is still sent but doesn't notify
+    /// anyone, so this isn't checked in [`Reply::check_permissions`], set this
+    /// when finding the permissions to request when inviting the bot
+    pub mention_everyone: bool,
+    /// Whether the reply is sent in a thread
+    pub thread: bool,
+}
+
+/// Defines what to do when the content of the reply is too long for a message,
+/// used in [`Reply::content_overflow`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentOverflow {
+    /// Send the content as a text attachment, with the start of the content as
+    /// the message's content
+    Attachment,
+    /// Split the content into multiple messages
+    ///
+    /// The content is split at line boundaries if possible, then at word
+    /// boundaries, code blocks are closed at the end of a message and reopened
+    /// in the next one
+    ///
+    /// Only the first message replies to [`Reply::message_reference`] and only
+    /// the last message has the embeds, components, attachments and stickers
+    Split,
+}
+
 /// Defines what to do when the reference message doesn't exist
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MissingMessageReferenceHandleMethod {
@@ -45,6 +110,10 @@ pub enum MissingMessageReferenceHandleMethod {
 /// [`InteractionHandle::reply`]: crate::interaction::InteractionHandle::reply
 /// [`ReplyHandle`]: crate::message::ReplyHandle
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "the bools are independent options set by the builder methods"
+)]
 pub struct Reply {
     /// The allowed mentions of the reply
     ///
@@ -55,10 +124,14 @@ pub struct Reply {
     pub attachments: Vec<Attachment>,
     /// See [`Reply::avatar_url`]
     pub avatar_url: Option<String>,
+    /// See [`Reply::check_permissions`]
+    pub check_permissions: bool,
     /// The components of the reply
     pub components: Vec<Component>,
     /// The content of the reply
     pub content: String,
+    /// See [`Reply::content_overflow`]
+    pub content_overflow: Option<ContentOverflow>,
     /// The embeds of the reply
     pub embeds: Vec<Embed>,
     /// The flags of the reply
@@ -111,6 +184,48 @@ impl Reply {
         self
     }
 
+    /// Add a button to the reply
+    ///
+    /// The button is added to the last action row if it only has buttons and
+    /// has space for it, a new action row is added otherwise
+    ///
+    /// More than 5 action rows are reported in [`Reply::validate`], use
+    /// [`action_rows`] to lay out components with an error instead
+    ///
+    /// [`action_rows`]: crate::component::action_rows
+    #[must_use]
+    pub fn button(mut self, button: ButtonBuilder) -> Self {
+        push_to_rows(&mut self.components, button.into());
+        self
+    }
+
+    /// Check that the bot has the permissions required to send the reply
+    /// before creating the message, returning [`Error::MissingPermissions`]
+    /// with the missing permissions instead of a generic permission error
+    ///
+    /// The permissions are calculated with [`Bot::permissions_in`], so
+    /// without the `cache` feature, or when the data isn't cached, each
+    /// message created with this makes these requests before creating it:
+    /// - Getting the channel
+    /// - Getting the parent channel if the channel is a thread
+    /// - Getting the guild and the bot's member to calculate its permissions
+    /// - Getting the guild's stickers if the reply has stickers
+    ///
+    /// To avoid this, check the permissions once with
+    /// [`Bot::permission_calculator`] and [`Reply::required_permissions`]
+    /// instead
+    ///
+    /// Only used when creating messages
+    ///
+    /// [`Error::MissingPermissions`]: crate::error::Error::MissingPermissions
+    /// [`Bot::permissions_in`]: crate::Bot::permissions_in
+    /// [`Bot::permission_calculator`]: crate::Bot::permission_calculator
+    #[must_use]
+    pub const fn check_permissions(mut self) -> Self {
+        self.check_permissions = true;
+        self
+    }
+
     /// Add a component to the reply
     #[must_use]
     pub fn component(mut self, component: Component) -> Self {
@@ -127,6 +242,20 @@ impl Reply {
         self
     }
 
+    /// Set what to do when the content is longer than Discord's limit
+    ///
+    /// By default, creating a message with too long content fails with
+    /// [`Error::MessageValidation`]
+    ///
+    /// Only used when creating messages
+    ///
+    /// [`Error::MessageValidation`]: crate::error::Error::MessageValidation
+    #[must_use]
+    pub const fn content_overflow(mut self, overflow: ContentOverflow) -> Self {
+        self.content_overflow = Some(overflow);
+        self
+    }
+
     /// Add an embed to the reply
     #[must_use]
     pub fn embed(mut self, embed: Embed) -> Self {
@@ -134,6 +263,26 @@ impl Reply {
         self
     }
 
+    /// Build an embed with [`EmbedBuilder`] and add it to the reply
+    ///
+    /// The embed isn't validated here, use [`Reply::validate`] or
+    /// [`EmbedBuilder::build`] with [`Reply::embed`] to validate it
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sparkle_convenience::reply::Reply;
+    ///
+    /// let reply = Reply::new().embed_with(|embed| embed.title("Pong!").field("Latency", "42ms"));
+    ///
+    /// assert_eq!(reply.embeds.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn embed_with<F: FnOnce(EmbedBuilder) -> EmbedBuilder>(mut self, build: F) -> Self {
+        self.embeds.push(build(EmbedBuilder::new()).into());
+        self
+    }
+
     /// Make the reply ephemeral
     ///
     /// Only used in interactions
@@ -154,6 +303,17 @@ impl Reply {
         self
     }
 
+    fn mentions_everyone(&self, default_allowed_mentions: Option<&AllowedMentions>) -> bool {
+        let allowed_mentions = self
+            .allowed_mentions
+            .as_ref()
+            .map_or(default_allowed_mentions, Option::as_ref);
+
+        (self.content.contains("@everyone") || self.content.contains("@here"))
+            && allowed_mentions
+                .is_none_or(|mentions| mentions.parse.contains(&MentionType::Everyone))
+    }
+
     /// Set the message reference of the reply, this is what's done in the
     /// Discord client using the `Reply` button
     ///
@@ -180,6 +340,7 @@ impl Reply {
     pub const fn new() -> Self {
         Self {
             content: String::new(),
+            content_overflow: None,
             embeds: vec![],
             components: vec![],
             attachments: vec![],
@@ -193,6 +354,7 @@ impl Reply {
             missing_message_reference_handle_method: None,
             username: None,
             avatar_url: None,
+            check_permissions: false,
             thread_id: None,
             thread_name: None,
             wait: false,
@@ -208,6 +370,132 @@ impl Reply {
         self
     }
 
+    /// Return the replies to send instead of this one if its content is too
+    /// long and [`Reply::content_overflow`] is set
+    pub(crate) fn overflow_replies(&self) -> Option<Vec<Self>> {
+        let overflow = self.content_overflow?;
+        if self.content.chars().count() <= MESSAGE_CONTENT_LENGTH_MAX {
+            return None;
+        }
+
+        match overflow {
+            ContentOverflow::Attachment => {
+                let id = self
+                    .attachments
+                    .iter()
+                    .map(|attachment| attachment.id.saturating_add(1))
+                    .max()
+                    .unwrap_or(0);
+
+                let mut reply = self.clone();
+                reply.content =
+                    format!("{}...", self.content.chars().take(100).collect::<String>());
+                reply.attachments.push(Attachment::from_bytes(
+                    "content.txt".to_owned(),
+                    self.content.clone().into_bytes(),
+                    id,
+                ));
+
+                Some(vec![reply])
+            }
+            ContentOverflow::Split => {
+                let chunks = split::split_text(&self.content, MESSAGE_CONTENT_LENGTH_MAX);
+                let last_index = chunks.len().saturating_sub(1);
+
+                Some(
+                    chunks
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, chunk)| {
+                            let mut reply = if index == last_index {
+                                self.clone()
+                            } else {
+                                Self {
+                                    allowed_mentions: self.allowed_mentions.clone(),
+                                    flags: self.flags,
+                                    message_reference: self.message_reference,
+                                    missing_message_reference_handle_method: self
+                                        .missing_message_reference_handle_method,
+                                    nonce: self.nonce,
+                                    tts: self.tts,
+                                    ..Self::new()
+                                }
+                            };
+                            if index != 0 {
+                                reply.message_reference = None;
+                                reply.missing_message_reference_handle_method = None;
+                                reply.nonce = None;
+                            }
+                            reply.content = chunk;
+
+                            reply
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Return the permissions required to send the reply in the given
+    /// context
+    ///
+    /// - [`Permissions::SEND_MESSAGES`], or
+    ///   [`Permissions::SEND_MESSAGES_IN_THREADS`] in threads
+    /// - [`Permissions::EMBED_LINKS`] if the reply has embeds
+    /// - [`Permissions::ATTACH_FILES`] if the reply has attachments
+    /// - [`Permissions::SEND_TTS_MESSAGES`] if the reply is TTS
+    /// - [`Permissions::MENTION_EVERYONE`] if
+    ///   [`PermissionContext::mention_everyone`] is set, the content mentions
+    ///   `@everyone` or `@here` and the allowed mentions allow it
+    /// - [`Permissions::READ_MESSAGE_HISTORY`] if the reply has a message
+    ///   reference
+    /// - [`Permissions::USE_EXTERNAL_STICKERS`] if the reply has stickers that
+    ///   aren't in [`PermissionContext::guild_sticker_ids`]
+    ///
+    /// This is what [`Reply::check_permissions`] checks, it can also be used
+    /// to find the permissions to request when inviting the bot, with
+    /// [`PermissionContext::mention_everyone`] set
+    #[must_use]
+    pub fn required_permissions(&self, context: PermissionContext<'_>) -> Permissions {
+        let mut permissions = if context.thread {
+            Permissions::SEND_MESSAGES_IN_THREADS
+        } else {
+            Permissions::SEND_MESSAGES
+        };
+
+        if !self.embeds.is_empty() {
+            permissions.insert(Permissions::EMBED_LINKS);
+        }
+        if !self.attachments.is_empty() {
+            permissions.insert(Permissions::ATTACH_FILES);
+        }
+        if self.tts {
+            permissions.insert(Permissions::SEND_TTS_MESSAGES);
+        }
+        if context.mention_everyone && self.mentions_everyone(context.default_allowed_mentions) {
+            permissions.insert(Permissions::MENTION_EVERYONE);
+        }
+        if self.message_reference.is_some() {
+            permissions.insert(Permissions::READ_MESSAGE_HISTORY);
+        }
+        if self.sticker_ids.iter().any(|sticker_id| {
+            !context
+                .guild_sticker_ids
+                .is_some_and(|guild_sticker_ids| guild_sticker_ids.contains(sticker_id))
+        }) {
+            permissions.insert(Permissions::USE_EXTERNAL_STICKERS);
+        }
+
+        permissions
+    }
+
+    /// Add a select menu to the reply in its own action row
+    #[must_use]
+    pub fn select_menu(mut self, select_menu: SelectMenuBuilder) -> Self {
+        push_to_rows(&mut self.components, select_menu.into());
+        self
+    }
+
     /// Add a sticker to the reply
     ///
     /// Only used when creating messages
@@ -263,6 +551,85 @@ impl Reply {
         self
     }
 
+    /// Run all the validations Discord does on the reply, returning every
+    /// problem found instead of only the first one
+    ///
+    /// The content isn't checked if [`Reply::content_overflow`] is set
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationReport`] with the problems found if the reply is
+    /// invalid
+    pub fn validate(&self) -> Result<(), ValidationReport> {
+        let mut errors = vec![];
+
+        if self.content.is_empty()
+            && self.embeds.is_empty()
+            && self.components.is_empty()
+            && self.attachments.is_empty()
+            && self.sticker_ids.is_empty()
+        {
+            errors.push(ReplyValidationError::Empty);
+        }
+
+        if self.content_overflow.is_none() {
+            errors.extend(message::content(&self.content).err().map(Into::into));
+        }
+
+        if self.embeds.len() > EMBED_COUNT_LIMIT {
+            errors.push(ReplyValidationError::TooManyEmbeds(self.embeds.len()));
+        }
+        errors.extend(self.embeds.iter().enumerate().filter_map(|(index, embed)| {
+            embed::embed(embed)
+                .err()
+                .map(|source| ReplyValidationError::Embed { index, source })
+        }));
+        let embeds_len = self
+            .embeds
+            .iter()
+            .map(embed::chars)
+            .fold(0, usize::saturating_add);
+        if embeds_len > EMBED_TOTAL_LENGTH {
+            errors.push(ReplyValidationError::EmbedsTooLong(embeds_len));
+        }
+
+        if self.components.len() > COMPONENT_COUNT {
+            errors.push(ReplyValidationError::TooManyComponents(
+                self.components.len(),
+            ));
+        }
+        errors.extend(
+            self.components
+                .iter()
+                .enumerate()
+                .filter_map(|(index, component)| {
+                    component::component(component)
+                        .err()
+                        .map(|source| ReplyValidationError::Component { index, source })
+                }),
+        );
+
+        errors.extend(
+            self.attachments
+                .iter()
+                .filter_map(|attachment| message::attachment(attachment).err().map(Into::into)),
+        );
+        errors.extend(
+            message::sticker_ids(&self.sticker_ids)
+                .err()
+                .map(Into::into),
+        );
+        if let Some(username) = &self.username {
+            errors.extend(request::webhook_username(username).err().map(Into::into));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationReport(errors))
+        }
+    }
+
     /// Wait for the message to be sent
     ///
     /// Only used when executing webhooks
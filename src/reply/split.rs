@@ -0,0 +1,138 @@
+//! Splitting text that's too long for Discord into chunks
+
+use std::mem;
+
+const CODE_FENCE: &str = "```";
+
+struct Splitter {
+    chunks: Vec<String>,
+    current: String,
+    current_len: usize,
+    fence: Option<String>,
+    /// Returns the length of the text, in characters or bytes
+    len: fn(&str) -> usize,
+    limit: usize,
+}
+
+impl Splitter {
+    /// Return whether the text fits in the current chunk, keeping space for
+    /// closing the code block that's open after it
+    fn fits(&self, text: &str, fence_after: Option<&String>) -> bool {
+        let closing_len = fence_after.map_or(0, |_| CODE_FENCE.len().saturating_add(1));
+
+        self.current_len
+            .saturating_add((self.len)(text))
+            .saturating_add(closing_len)
+            <= self.limit
+    }
+
+    fn flush(&mut self) {
+        let mut chunk = mem::take(&mut self.current);
+        self.current_len = 0;
+
+        let only_fence = self
+            .fence
+            .as_ref()
+            .is_some_and(|fence| chunk.trim() == fence);
+        if self.fence.is_some() {
+            chunk.truncate(chunk.trim_end_matches('\n').len());
+            chunk.push('\n');
+            chunk.push_str(CODE_FENCE);
+        }
+        if !only_fence && !chunk.trim().is_empty() {
+            self.chunks.push(chunk);
+        }
+
+        if let Some(fence) = self.fence.clone() {
+            self.push_str(&fence);
+            self.push_str("\n");
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        let fence_after = if line.trim_start().starts_with(CODE_FENCE) {
+            match self.fence {
+                Some(_) => None,
+                None => Some(line.trim().to_owned()),
+            }
+        } else {
+            self.fence.clone()
+        };
+
+        if !self.fits(line, fence_after.as_ref()) {
+            self.flush();
+        }
+
+        if self.fits(line, fence_after.as_ref()) {
+            self.push_str(line);
+        } else {
+            for word in line.split_inclusive(' ') {
+                self.push_word(word);
+            }
+        }
+
+        self.fence = fence_after;
+    }
+
+    fn push_str(&mut self, text: &str) {
+        self.current.push_str(text);
+        self.current_len = self.current_len.saturating_add((self.len)(text));
+    }
+
+    fn push_word(&mut self, word: &str) {
+        if !self.fits(word, self.fence.as_ref()) {
+            self.flush();
+        }
+
+        if self.fits(word, self.fence.as_ref()) {
+            self.push_str(word);
+            return;
+        }
+
+        for character in word.chars() {
+            let mut buffer = [0; 4];
+            let encoded = character.encode_utf8(&mut buffer);
+            if !self.fits(encoded, self.fence.as_ref()) {
+                self.flush();
+            }
+            self.push_str(encoded);
+        }
+    }
+}
+
+/// Split the text into chunks of at most `limit` characters
+///
+/// The text is split at line boundaries if possible, then at word boundaries
+/// and finally at any character
+///
+/// If a chunk ends inside a code block, the code block is closed at the end of
+/// the chunk and reopened with the same language at the start of the next one
+pub(crate) fn split_text(text: &str, limit: usize) -> Vec<String> {
+    split_text_with(text, limit, |chunk| chunk.chars().count())
+}
+
+/// Split the text into chunks of at most `limit` bytes, the same way as
+/// [`split_text`]
+///
+/// Used for embeds, whose total length Twilight validates in bytes
+pub(crate) fn split_text_bytes(text: &str, limit: usize) -> Vec<String> {
+    split_text_with(text, limit, str::len)
+}
+
+fn split_text_with(text: &str, limit: usize, len: fn(&str) -> usize) -> Vec<String> {
+    let mut splitter = Splitter {
+        chunks: vec![],
+        current: String::new(),
+        current_len: 0,
+        fence: None,
+        len,
+        limit,
+    };
+
+    for line in text.split_inclusive('\n') {
+        splitter.push_line(line);
+    }
+    splitter.flush();
+
+    splitter.chunks
+}
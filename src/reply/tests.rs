@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+
+use twilight_model::{
+    channel::message::{AllowedMentions, Embed, MentionType},
+    guild::Permissions,
+    id::Id,
+};
+
+use crate::{
+    error::ReplyValidationError,
+    reply::{
+        ContentOverflow,
+        MissingMessageReferenceHandleMethod,
+        PermissionContext,
+        Reply,
+        split::split_text,
+    },
+};
+
+fn embed() -> Embed {
+    Embed {
+        author: None,
+        color: None,
+        description: Some("description".to_owned()),
+        fields: vec![],
+        footer: None,
+        image: None,
+        kind: "rich".to_owned(),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: None,
+        url: None,
+        video: None,
+    }
+}
+
+#[test]
+fn required_permissions() {
+    assert_eq!(
+        Reply::new()
+            .content("hi")
+            .required_permissions(PermissionContext::default()),
+        Permissions::SEND_MESSAGES
+    );
+
+    assert_eq!(
+        Reply::new()
+            .embed(embed())
+            .tts()
+            .message_reference(Id::new(1), MissingMessageReferenceHandleMethod::Ignore)
+            .required_permissions(PermissionContext {
+                thread: true,
+                ..PermissionContext::default()
+            }),
+        Permissions::SEND_MESSAGES_IN_THREADS
+            | Permissions::EMBED_LINKS
+            | Permissions::SEND_TTS_MESSAGES
+            | Permissions::READ_MESSAGE_HISTORY
+    );
+}
+
+#[test]
+fn required_permissions_stickers() {
+    let reply = Reply::new().sticker(Id::new(1));
+    let guild_sticker_ids = HashSet::from([Id::new(1)]);
+
+    assert!(
+        reply
+            .required_permissions(PermissionContext::default())
+            .contains(Permissions::USE_EXTERNAL_STICKERS)
+    );
+    assert!(
+        !reply
+            .required_permissions(PermissionContext {
+                guild_sticker_ids: Some(&guild_sticker_ids),
+                ..PermissionContext::default()
+            })
+            .contains(Permissions::USE_EXTERNAL_STICKERS)
+    );
+}
+
+#[test]
+fn required_permissions_mention_everyone() {
+    let no_mentions = AllowedMentions::default();
+    let everyone = AllowedMentions {
+        parse: vec![MentionType::Everyone],
+        ..AllowedMentions::default()
+    };
+    let context = PermissionContext {
+        mention_everyone: true,
+        ..PermissionContext::default()
+    };
+
+    let reply = Reply::new().content("hi @everyone");
+    assert_eq!(
+        reply.required_permissions(PermissionContext::default()),
+        Permissions::SEND_MESSAGES
+    );
+    assert!(
+        reply
+            .required_permissions(context)
+            .contains(Permissions::MENTION_EVERYONE)
+    );
+    assert!(
+        !reply
+            .required_permissions(PermissionContext {
+                default_allowed_mentions: Some(&no_mentions),
+                ..context
+            })
+            .contains(Permissions::MENTION_EVERYONE)
+    );
+    assert!(
+        reply
+            .allowed_mentions(Some(everyone))
+            .required_permissions(PermissionContext {
+                default_allowed_mentions: Some(&no_mentions),
+                ..context
+            })
+            .contains(Permissions::MENTION_EVERYONE)
+    );
+    assert!(
+        !Reply::new()
+            .content("hi everyone")
+            .required_permissions(context)
+            .contains(Permissions::MENTION_EVERYONE)
+    );
+}
+
+#[test]
+fn split_text_lines_and_words() {
+    assert_eq!(split_text("short", 10), vec!["short"]);
+
+    assert_eq!(
+        split_text("first line\nsecond line\n", 15),
+        vec!["first line\n", "second line\n"]
+    );
+
+    assert_eq!(
+        split_text("some words that are too long", 10),
+        vec!["some ", "words ", "that are ", "too long"]
+    );
+
+    assert_eq!(split_text("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+}
+
+#[test]
+fn split_text_code_blocks() {
+    let chunks = split_text("text\n```rust\nlet a = 1;\nlet b = 2;\n```\nafter", 30);
+
+    assert_eq!(
+        chunks,
+        vec![
+            "text\n```rust\nlet a = 1;\n```",
+            "```rust\nlet b = 2;\n```\nafter"
+        ]
+    );
+    assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 30));
+}
+
+#[test]
+fn overflow_replies() {
+    let long_content = "word ".repeat(500);
+
+    assert!(
+        Reply::new()
+            .content(&long_content)
+            .overflow_replies()
+            .is_none()
+    );
+    assert!(
+        Reply::new()
+            .content("short")
+            .content_overflow(ContentOverflow::Split)
+            .overflow_replies()
+            .is_none()
+    );
+
+    let split = Reply::new()
+        .content(&long_content)
+        .embed(embed())
+        .message_reference(Id::new(1), MissingMessageReferenceHandleMethod::Ignore)
+        .content_overflow(ContentOverflow::Split)
+        .overflow_replies()
+        .unwrap();
+    assert_eq!(split.len(), 2);
+    let first = split.first().unwrap();
+    let last = split.last().unwrap();
+    assert!(first.embeds.is_empty());
+    assert_eq!(first.message_reference, Some(Id::new(1)));
+    assert_eq!(last.embeds.len(), 1);
+    assert_eq!(last.message_reference, None);
+    assert_eq!(
+        split
+            .iter()
+            .map(|reply| reply.content.as_str())
+            .collect::<String>(),
+        long_content
+    );
+
+    let attachment = Reply::new()
+        .content(&long_content)
+        .content_overflow(ContentOverflow::Attachment)
+        .overflow_replies()
+        .unwrap();
+    assert_eq!(attachment.len(), 1);
+    assert_eq!(attachment.first().unwrap().attachments.len(), 1);
+}
+
+#[test]
+fn validate() {
+    Reply::new().content("hi").validate().unwrap();
+
+    let report = Reply::new()
+        .content("a".repeat(2001))
+        .username("")
+        .validate()
+        .unwrap_err();
+    assert_eq!(report.0.len(), 2);
+    assert!(matches!(
+        report.0.first(),
+        Some(ReplyValidationError::Message(_))
+    ));
+    assert!(matches!(
+        report.0.last(),
+        Some(ReplyValidationError::Request(_))
+    ));
+
+    Reply::new()
+        .content("a".repeat(2001))
+        .content_overflow(ContentOverflow::Split)
+        .validate()
+        .unwrap();
+
+    let empty_report = Reply::new().validate().unwrap_err();
+    assert!(matches!(
+        empty_report.0.as_slice(),
+        [ReplyValidationError::Empty]
+    ));
+}
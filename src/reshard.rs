@@ -0,0 +1,261 @@
+//! Restarting the bot's shards with the number of shards Discord recommends
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, sync::Arc};
+
+use futures_util::StreamExt as _;
+use tokio::task::JoinHandle;
+#[cfg(feature = "cache")]
+use twilight_cache_inmemory::InMemoryCache;
+use twilight_gateway::{CloseFrame, Config, Shard, ShardId, stream::ShardEventStream};
+use twilight_http::Client;
+use twilight_model::gateway::event::Event;
+
+use crate::{Bot, Shards, builder::ShardSelection, error::Error, status::ShardTracker};
+
+/// The shards started with the number of shards Discord recommends, returned
+/// from the task [`Shards::spawn_reshard`] spawns
+///
+/// Pass this to [`Shards::replace`] to swap to the new shards
+#[derive(Debug)]
+pub struct Resharded {
+    /// The events the new shards received before all of them were ready,
+    /// such as interactions and messages, in the order they were received
+    ///
+    /// These already updated the cache and the status of the new shards,
+    /// handle them before receiving events from the new shards
+    pub events: Vec<(ShardId, Event)>,
+    /// The status of the new shards, replacing the current shards' in
+    /// [`Bot::shard_tracker`] in [`Shards::replace`]
+    shard_tracker: ShardTracker,
+    /// The new shards
+    pub shards: Shards,
+}
+
+impl Shards {
+    fn config(&self) -> Option<Config> {
+        self.0.first().map(|shard| shard.config().clone())
+    }
+
+    /// Close the current shards and replace them with the given shards,
+    /// returning the events the new shards received before they were ready
+    ///
+    /// The current shards are closed with [`CloseFrame::NORMAL`], so their
+    /// sessions are invalidated, and are then removed from
+    /// [`Bot::shard_tracker`] in favor of the new shards
+    ///
+    /// Stop receiving events from the current shards before calling this,
+    /// events received from them afterwards would track them again
+    pub async fn replace(&mut self, bot: &Bot, resharded: Resharded) -> Vec<(ShardId, Event)> {
+        for shard in &mut self.0 {
+            let _close_res = shard.close(CloseFrame::NORMAL).await;
+        }
+
+        bot.shard_tracker
+            .replace(self.0.iter().map(Shard::id), &resharded.shard_tracker);
+        *self = resharded.shards;
+
+        resharded.events
+    }
+
+    /// Start the number of shards Discord currently recommends, wait until all
+    /// of them are ready and replace the current shards with them, returning
+    /// the events the new shards received before they were ready
+    ///
+    /// Returns `None` without doing anything if the number of shards didn't
+    /// change
+    ///
+    /// The shard selection the bot was built with is kept, scaled to the new
+    /// number of shards, for example the second half of the shards are still
+    /// started if the bot was built with [`ShardSelection::Range`] covering
+    /// the second half of the shards
+    ///
+    /// The new shards are created with the configuration of the current
+    /// shards, so create a new event stream with [`Shards::events`] after this
+    ///
+    /// The returned events, such as [`Event::InteractionCreate`], already
+    /// updated the cache and [`Bot::shard_tracker`], handle them before
+    /// receiving events from the new event stream, see [`Resharded::events`]
+    ///
+    /// # Warning
+    ///
+    /// The current shards don't receive events while the new shards are
+    /// starting, which may take a while for bots with many shards, use
+    /// [`Shards::spawn_reshard`] and [`Shards::replace`] to keep receiving
+    /// events in the meantime
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if getting the
+    /// recommended number of shards fails
+    ///
+    /// Returns [`Error::InvalidShardSelection`] if the scaled shard selection
+    /// is empty
+    ///
+    /// Returns [`Error::ReceiveMessage`] if a new shard is fatally closed
+    /// before it's ready
+    pub async fn reshard(&mut self, bot: &Bot) -> Result<Option<Vec<(ShardId, Event)>>, Error> {
+        let (Some(config), Some(total)) = (self.config(), self.total()) else {
+            return Ok(None);
+        };
+
+        let resharded = start_recommended(
+            &bot.http,
+            config,
+            bot.shard_selection,
+            total,
+            #[cfg(feature = "cache")]
+            &bot.cache,
+        )
+        .await?;
+
+        match resharded {
+            Some(new_shards) => Ok(Some(self.replace(bot, new_shards).await)),
+            None => Ok(None),
+        }
+    }
+
+    /// Start the number of shards Discord currently recommends in a new task,
+    /// returning them once all of them are ready
+    ///
+    /// The task returns `None` if the number of shards didn't change, pass the
+    /// shards it returns to [`Shards::replace`] to swap to them
+    ///
+    /// The shard selection and the events received while waiting are handled
+    /// the same way as in [`Shards::reshard`], [`Bot::shard_tracker`] keeps
+    /// tracking the current shards until [`Shards::replace`] is called
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn reshard(
+    /// #     bot: sparkle_convenience::Bot,
+    /// #     mut shards: sparkle_convenience::Shards,
+    /// # ) -> Result<(), Box<dyn std::error::Error>> {
+    /// let reshard = shards.spawn_reshard(&bot);
+    ///
+    /// // keep receiving events from `shards` until `reshard` is finished
+    ///
+    /// if let Some(resharded) = reshard.await?? {
+    ///     for (shard_id, event) in shards.replace(&bot, resharded).await {
+    ///         // handle the events received while the new shards were starting
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The task returns the same errors as [`Shards::reshard`]
+    pub fn spawn_reshard(&self, bot: &Bot) -> JoinHandle<Result<Option<Resharded>, Error>> {
+        let http = Arc::clone(&bot.http);
+        let config = self.config();
+        let total = self.total();
+        let selection = bot.shard_selection;
+        #[cfg(feature = "cache")]
+        let cache = Arc::clone(&bot.cache);
+
+        tokio::spawn(async move {
+            match (config, total) {
+                (Some(shard_config), Some(current_total)) => {
+                    start_recommended(
+                        &http,
+                        shard_config,
+                        selection,
+                        current_total,
+                        #[cfg(feature = "cache")]
+                        &cache,
+                    )
+                    .await
+                }
+                _ => Ok(None),
+            }
+        })
+    }
+
+    fn total(&self) -> Option<u64> {
+        self.0.first().map(|shard| shard.id().total())
+    }
+}
+
+/// Return the selection to start the shards with, `None` if the number of
+/// shards didn't change
+fn resharded_selection(
+    selection: ShardSelection,
+    current_total: u64,
+    recommended_total: u64,
+) -> Option<ShardSelection> {
+    (recommended_total != current_total).then(|| selection.rescaled(recommended_total))
+}
+
+async fn start_recommended(
+    http: &Client,
+    config: Config,
+    selection: ShardSelection,
+    current_total: u64,
+    #[cfg(feature = "cache")] cache: &InMemoryCache,
+) -> Result<Option<Resharded>, Error> {
+    let recommended_total = http.gateway().authed().await?.model().await?.shards;
+
+    let Some(new_selection) = resharded_selection(selection, current_total, recommended_total)
+    else {
+        return Ok(None);
+    };
+
+    let mut shards = new_selection.create(http, config, &HashMap::new()).await?;
+
+    let shard_tracker = ShardTracker::default();
+    let events = wait_until_ready(
+        &mut shards,
+        &shard_tracker,
+        #[cfg(feature = "cache")]
+        cache,
+    )
+    .await?;
+
+    Ok(Some(Resharded {
+        events,
+        shard_tracker,
+        shards: Shards(shards),
+    }))
+}
+
+/// Receive events from the shards until all of them are ready, returning the
+/// events other than [`Event::Ready`] they received
+async fn wait_until_ready(
+    shards: &mut [Shard],
+    shard_tracker: &ShardTracker,
+    #[cfg(feature = "cache")] cache: &InMemoryCache,
+) -> Result<Vec<(ShardId, Event)>, Error> {
+    let mut not_ready = shards.len();
+    let mut events = ShardEventStream::new(shards.iter_mut());
+    let mut buffered = vec![];
+
+    while not_ready > 0 {
+        let Some((shard, event_res)) = events.next().await else {
+            break;
+        };
+        shard_tracker.update(&shard);
+
+        match event_res {
+            Ok(event) => {
+                shard_tracker.track_event(shard.id(), &event);
+                #[cfg(feature = "cache")]
+                cache.update(&event);
+
+                if matches!(event, Event::Ready(_)) {
+                    not_ready = not_ready.saturating_sub(1);
+                } else {
+                    buffered.push((shard.id(), event));
+                }
+            }
+            Err(err) if err.is_fatal() => return Err(err.into()),
+            Err(_) => {}
+        }
+    }
+
+    Ok(buffered)
+}
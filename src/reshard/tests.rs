@@ -0,0 +1,124 @@
+use crate::{builder::ShardSelection, reshard::resharded_selection};
+
+#[test]
+fn resharded_selection_unchanged_total() {
+    for selection in [
+        ShardSelection::Bucket {
+            bucket_id: 1,
+            concurrency: 16,
+            total: 32,
+        },
+        ShardSelection::Range {
+            end: 16,
+            start: 0,
+            total: 32,
+        },
+        ShardSelection::Recommended,
+        ShardSelection::Total(32),
+    ] {
+        assert_eq!(resharded_selection(selection, 32, 32), None);
+    }
+}
+
+#[test]
+fn resharded_selection_bucket() {
+    assert_eq!(
+        resharded_selection(
+            ShardSelection::Bucket {
+                bucket_id: 3,
+                concurrency: 16,
+                total: 32,
+            },
+            32,
+            48
+        ),
+        Some(ShardSelection::Bucket {
+            bucket_id: 3,
+            concurrency: 16,
+            total: 48,
+        })
+    );
+}
+
+#[test]
+fn resharded_selection_range() {
+    assert_eq!(
+        resharded_selection(
+            ShardSelection::Range {
+                end: 32,
+                start: 16,
+                total: 32,
+            },
+            32,
+            48
+        ),
+        Some(ShardSelection::Range {
+            end: 48,
+            start: 24,
+            total: 48,
+        })
+    );
+    assert_eq!(
+        resharded_selection(
+            ShardSelection::Range {
+                end: 6,
+                start: 3,
+                total: 9,
+            },
+            9,
+            4
+        ),
+        Some(ShardSelection::Range {
+            end: 2,
+            start: 1,
+            total: 4,
+        })
+    );
+}
+
+#[test]
+fn resharded_selection_range_processes() {
+    let process_count = 3;
+    let ranges = (0..process_count)
+        .map(|process_id| {
+            resharded_selection(
+                ShardSelection::for_process(process_id, process_count, 10).unwrap(),
+                10,
+                16,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        ranges,
+        [
+            Some(ShardSelection::Range {
+                end: 6,
+                start: 0,
+                total: 16,
+            }),
+            Some(ShardSelection::Range {
+                end: 11,
+                start: 6,
+                total: 16,
+            }),
+            Some(ShardSelection::Range {
+                end: 16,
+                start: 11,
+                total: 16,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn resharded_selection_total() {
+    assert_eq!(
+        resharded_selection(ShardSelection::Recommended, 32, 48),
+        Some(ShardSelection::Total(48))
+    );
+    assert_eq!(
+        resharded_selection(ShardSelection::Total(32), 32, 48),
+        Some(ShardSelection::Total(48))
+    );
+}
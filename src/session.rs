@@ -0,0 +1,160 @@
+//! Saving the shards' gateway sessions to resume them after restarting
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Write as _},
+    fs::{self, File},
+    io::{self, Write as _},
+    path::PathBuf,
+};
+
+use twilight_gateway::{CloseFrame, Session, ShardId};
+
+use crate::Shards;
+
+/// Implemented on types the shards' sessions can be saved to and loaded from
+///
+/// Pass this to [`BotBuilder::session_store`] to resume the saved sessions
+/// when starting and to [`Shards::close_and_save_sessions`] to save them when
+/// shutting down
+///
+/// [`BotBuilder::session_store`]: crate::builder::BotBuilder::session_store
+pub trait SessionStore: Debug + Send + Sync {
+    /// Load the saved sessions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if loading the sessions fails
+    fn load(&self) -> io::Result<HashMap<ShardId, Session>>;
+
+    /// Save the given sessions, overwriting the previously saved ones
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if saving the sessions fails
+    fn save(&self, sessions: &HashMap<ShardId, Session>) -> io::Result<()>;
+}
+
+/// A file to save the shards' sessions to
+///
+/// Each line of the file is the shard's number, the total number of shards,
+/// the sequence and the session ID separated by spaces
+///
+/// The sessions are written to a temporary file next to it, named after it
+/// with `.tmp` appended, which then replaces it, so the saved sessions aren't
+/// lost if the bot is killed while saving them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionFile {
+    /// The path of the file
+    pub path: PathBuf,
+}
+
+impl SessionFile {
+    /// Create a new session file with the given path
+    #[must_use]
+    pub fn new<T: Into<PathBuf>>(path: T) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Return the path of the file the sessions are written to before
+    /// replacing the session file with it
+    fn temp_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".tmp");
+        path.into()
+    }
+}
+
+impl SessionStore for SessionFile {
+    /// Returns an empty map if the file doesn't exist, invalid lines are
+    /// ignored
+    fn load(&self) -> io::Result<HashMap<ShardId, Session>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(err) => return Err(err),
+        };
+
+        Ok(content.lines().filter_map(parse_session_line).collect())
+    }
+
+    fn save(&self, sessions: &HashMap<ShardId, Session>) -> io::Result<()> {
+        let mut sorted_sessions = sessions.iter().collect::<Vec<_>>();
+        sorted_sessions.sort_by_key(|(shard_id, _)| shard_id.number());
+
+        let content =
+            sorted_sessions
+                .into_iter()
+                .fold(String::new(), |mut content, (shard_id, session)| {
+                    let _write_res = writeln!(
+                        content,
+                        "{} {} {} {}",
+                        shard_id.number(),
+                        shard_id.total(),
+                        session.sequence(),
+                        session.id()
+                    );
+                    content
+                });
+
+        let temp_path = self.temp_path();
+        let mut file = File::create(&temp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+
+        fs::rename(temp_path, &self.path)
+    }
+}
+
+impl Shards {
+    /// Close the shards without invalidating their sessions and save the
+    /// sessions to the given store
+    ///
+    /// Shards that are already disconnected have their last session saved
+    ///
+    /// To also finish the bot's tasks, use [`Shards::shutdown`] instead
+    ///
+    /// # Errors
+    ///
+    /// Returns the error [`SessionStore::save`] returns
+    pub async fn close_and_save_sessions(&mut self, store: &dyn SessionStore) -> io::Result<()> {
+        store.save(&self.close_resumable().await)
+    }
+
+    pub(crate) async fn close_resumable(&mut self) -> HashMap<ShardId, Session> {
+        let mut sessions = HashMap::new();
+
+        for shard in &mut self.0 {
+            let last_session = shard.session().cloned();
+            let session = shard
+                .close(CloseFrame::RESUME)
+                .await
+                .ok()
+                .flatten()
+                .or(last_session);
+
+            if let Some(shard_session) = session {
+                sessions.insert(shard.id(), shard_session);
+            }
+        }
+
+        sessions
+    }
+}
+
+fn parse_session_line(line: &str) -> Option<(ShardId, Session)> {
+    let mut parts = line.split_whitespace();
+
+    let number = parts.next()?.parse().ok()?;
+    let total = parts.next()?.parse().ok()?;
+    let sequence = parts.next()?.parse().ok()?;
+    let session_id = parts.next()?;
+
+    Some((
+        ShardId::new_checked(number, total)?,
+        Session::new(sequence, session_id.to_owned()),
+    ))
+}
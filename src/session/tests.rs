@@ -0,0 +1,53 @@
+use std::{collections::HashMap, env, fs, process};
+
+use twilight_gateway::{Session, ShardId};
+
+use crate::session::{SessionFile, SessionStore as _, parse_session_line};
+
+#[test]
+fn session_file_round_trip() {
+    let path = env::temp_dir().join(format!("sparkle_convenience_sessions_{}", process::id()));
+    let file = SessionFile::new(&path);
+
+    assert!(file.load().unwrap().is_empty());
+
+    let sessions = HashMap::from([
+        (ShardId::new(0, 2), Session::new(5, "first".to_owned())),
+        (ShardId::new(1, 2), Session::new(8, "second".to_owned())),
+    ]);
+    file.save(&sessions).unwrap();
+
+    assert_eq!(file.load().unwrap(), sessions);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn session_file_replaced() {
+    let path = env::temp_dir().join(format!(
+        "sparkle_convenience_sessions_replaced_{}",
+        process::id()
+    ));
+    let file = SessionFile::new(&path);
+
+    let sessions = HashMap::from([(ShardId::new(0, 1), Session::new(5, "first".to_owned()))]);
+    file.save(&sessions).unwrap();
+
+    fs::write(file.temp_path(), "0 1 8 partial").unwrap();
+    assert_eq!(file.load().unwrap(), sessions);
+
+    let new_sessions = HashMap::from([(ShardId::new(0, 1), Session::new(8, "second".to_owned()))]);
+    file.save(&new_sessions).unwrap();
+
+    assert_eq!(file.load().unwrap(), new_sessions);
+    assert!(!file.temp_path().exists());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn session_line_invalid() {
+    assert!(parse_session_line("2 2 5 session").is_none());
+    assert!(parse_session_line("0 2 5").is_none());
+    assert!(parse_session_line("zero 2 5 session").is_none());
+}
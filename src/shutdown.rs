@@ -0,0 +1,131 @@
+//! Shutting down the bot gracefully
+
+#[cfg(test)]
+mod tests;
+
+use std::{future::Future, time::Duration};
+
+use tokio::{task::JoinHandle, time::timeout};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+use crate::{Bot, Shards, error::Error, session::SessionStore};
+
+/// Options for [`Shards::shutdown`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShutdownOptions<'store> {
+    /// How long to wait for the tasks spawned with [`Tasks::spawn`] to finish,
+    /// `None` to not wait for them
+    pub handler_timeout: Option<Duration>,
+    /// The store to save the shards' sessions to, so that they can be resumed
+    /// after restarting
+    pub session_store: Option<&'store dyn SessionStore>,
+}
+
+/// Tasks spawned by the bot, tracked so that they can be finished when
+/// shutting down
+///
+/// Set in [`Bot::tasks`]
+#[derive(Clone, Debug, Default)]
+pub struct Tasks {
+    handlers: TaskTracker,
+    internal: TaskTracker,
+    shutdown: CancellationToken,
+}
+
+impl Tasks {
+    /// Return whether [`Shards::shutdown`] was called
+    #[must_use]
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    /// Wait until [`Shards::shutdown`] is called
+    pub async fn shutting_down(&self) {
+        self.shutdown.cancelled().await;
+    }
+
+    /// Spawn a task, usually an event handler, that can be waited for in
+    /// [`Shards::shutdown`]
+    pub fn spawn<F: Future<Output = ()> + Send + 'static>(&self, task: F) -> JoinHandle<()> {
+        self.handlers.spawn(task)
+    }
+
+    /// Spawn a task that sleeps for the given duration, or until shutting down,
+    /// and then runs the given task
+    ///
+    /// These tasks are always waited for in [`Shards::shutdown`]
+    pub(crate) fn spawn_after<F: Future<Output = ()> + Send + 'static>(
+        &self,
+        after: Duration,
+        task: F,
+    ) {
+        let shutdown = self.shutdown.clone();
+
+        self.spawn_internal(async move {
+            let _timeout_res = timeout(after, shutdown.cancelled()).await;
+            task.await;
+        });
+    }
+
+    /// Spawn a task that is always waited for in [`Shards::shutdown`], so it
+    /// must stop when shutting down
+    pub(crate) fn spawn_internal<F: Future<Output = ()> + Send + 'static>(&self, task: F) {
+        self.internal.spawn(task);
+    }
+
+    pub(crate) fn start_shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// Wait for the tasks spawned with [`Tasks::spawn`] for at most the given
+    /// duration if it's `Some`, then wait for the internal tasks
+    pub(crate) async fn wait(&self, handler_timeout: Option<Duration>) {
+        if let Some(timeout_duration) = handler_timeout {
+            self.handlers.close();
+            let _timeout_res = timeout(timeout_duration, self.handlers.wait()).await;
+        }
+
+        self.internal.close();
+        self.internal.wait().await;
+    }
+}
+
+impl Shards {
+    /// Shut down the bot gracefully
+    ///
+    /// This should be called after you stop receiving events, for example
+    /// when `tokio::signal::ctrl_c` resolves
+    ///
+    /// In order:
+    /// - Marks the bot as shutting down, see [`Tasks::shutting_down`]
+    /// - Closes the shards without invalidating their sessions, saving the
+    ///   sessions to [`ShutdownOptions::session_store`]
+    /// - Waits for the tasks spawned with [`Tasks::spawn`] for at most
+    ///   [`ShutdownOptions::handler_timeout`]
+    /// - Deletes the messages that were going to be deleted with `delete_after`
+    ///   methods
+    /// - Logs the messages in [`Bot::log_queue`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if saving the sessions fails
+    ///
+    /// Returns the error [`Bot::flush_log_queue`] returns
+    ///
+    /// All steps are run even if one of them fails
+    pub async fn shutdown(&mut self, bot: &Bot, options: ShutdownOptions<'_>) -> Result<(), Error> {
+        bot.tasks.start_shutdown();
+
+        let sessions = self.close_resumable().await;
+        let save_res = options
+            .session_store
+            .map_or(Ok(()), |store| store.save(&sessions));
+
+        bot.tasks.wait(options.handler_timeout).await;
+
+        let flush_res = bot.flush_log_queue().await;
+
+        save_res?;
+        flush_res
+    }
+}
@@ -0,0 +1,67 @@
+use std::{
+    future::{self, Future},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    runtime::Builder,
+    time::{sleep, timeout},
+};
+
+use crate::shutdown::Tasks;
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn spawn_after_cancelled_on_shutdown() {
+    block_on(async {
+        let tasks = Tasks::default();
+        let ran = Arc::new(AtomicBool::new(false));
+
+        let task_ran = Arc::clone(&ran);
+        tasks.spawn_after(Duration::from_mins(1), async move {
+            task_ran.store(true, Ordering::Relaxed);
+        });
+        assert!(!tasks.is_shutting_down());
+
+        tasks.start_shutdown();
+        assert!(tasks.is_shutting_down());
+        timeout(Duration::from_secs(1), tasks.wait(None))
+            .await
+            .unwrap();
+
+        assert!(ran.load(Ordering::Relaxed));
+    });
+}
+
+#[test]
+fn wait_handlers_timeout() {
+    block_on(async {
+        let tasks = Tasks::default();
+        let finished = Arc::new(AtomicBool::new(false));
+
+        let task_finished = Arc::clone(&finished);
+        tasks.spawn(async move {
+            sleep(Duration::from_millis(10)).await;
+            task_finished.store(true, Ordering::Relaxed);
+        });
+        tasks.spawn(future::pending());
+
+        let start = Instant::now();
+        tasks.wait(Some(Duration::from_millis(100))).await;
+
+        assert!(finished.load(Ordering::Relaxed));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    });
+}
@@ -0,0 +1,306 @@
+//! Reporting the status of the shards and logging shards that are stuck
+//! reconnecting
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write as _,
+    mem,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use tokio::time::timeout;
+use twilight_gateway::{ConnectionStatus, Shard, ShardId};
+use twilight_model::{
+    gateway::event::Event,
+    id::{Id, marker::GuildMarker},
+};
+
+use crate::{Bot, Shards};
+
+/// The status of a shard, returned in [`Shards::status`] and
+/// [`ShardTracker::status`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShardStatus {
+    /// The connection status of the shard
+    pub connection: ConnectionStatus,
+    /// How long the shard has been disconnected, identifying or resuming for,
+    /// `None` if it's connected
+    pub disconnected_for: Option<Duration>,
+    /// The number of guilds on the shard, only tracked with
+    /// [`ShardTracker::track_event`]
+    pub guild_count: usize,
+    /// The ID of the shard
+    pub id: ShardId,
+    /// The average heartbeat latency of the shard, `None` if no heartbeats
+    /// were acknowledged yet
+    pub latency: Option<Duration>,
+    /// The number of times the shard reconnected after it was first connected
+    pub reconnects: u32,
+    /// How long ago the shard's current session started, `None` if it doesn't
+    /// have a session
+    pub session_age: Option<Duration>,
+}
+
+impl ShardStatus {
+    /// Return whether the shard has been disconnected for at least the given
+    /// duration
+    #[must_use]
+    pub fn is_stuck(&self, threshold: Duration) -> bool {
+        self.disconnected_for
+            .is_some_and(|disconnected_for| disconnected_for >= threshold)
+    }
+}
+
+/// Tracks the shards' sessions and reconnects over time
+///
+/// Set in [`Bot::shard_tracker`], it's cheap to clone and clones share the
+/// tracked data
+///
+/// Call [`ShardTracker::update`] with the shard after each event or error
+/// received from it and [`ShardTracker::track_event`] with each event, this is
+/// done automatically when using [`Shards::into_receiver`]
+#[derive(Clone, Debug, Default)]
+pub struct ShardTracker(Arc<Mutex<HashMap<ShardId, TrackedShard>>>);
+
+impl ShardTracker {
+    /// Return the number of guilds on all the tracked shards
+    #[must_use]
+    pub fn guild_count(&self) -> usize {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|tracked| tracked.guilds.len())
+            .sum()
+    }
+
+    /// Stop tracking the given shards and track the shards tracked by the
+    /// given tracker instead, leaving it empty
+    pub(crate) fn replace(&self, removed: impl IntoIterator<Item = ShardId>, tracker: &Self) {
+        let added = mem::take(&mut *tracker.0.lock().unwrap_or_else(PoisonError::into_inner));
+
+        let mut shards = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        for shard_id in removed {
+            shards.remove(&shard_id);
+        }
+        shards.extend(added);
+    }
+
+    /// Return the last tracked status of the shards, sorted by their IDs
+    ///
+    /// Unlike [`Shards::status`], this doesn't require access to the shards,
+    /// so it can be used while receiving events
+    #[must_use]
+    pub fn status(&self) -> Vec<ShardStatus> {
+        let now = Instant::now();
+        let mut statuses = self
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(shard_id, tracked)| tracked.status(*shard_id, now))
+            .collect::<Vec<_>>();
+        statuses.sort_by_key(|status| status.id.number());
+
+        statuses
+    }
+
+    /// Track the guilds on the shard that received the given event
+    pub fn track_event(&self, shard_id: ShardId, event: &Event) {
+        let mut shards = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let tracked = shards.entry(shard_id).or_default();
+
+        match event {
+            Event::GuildCreate(guild_create) => {
+                tracked.guilds.insert(guild_create.0.id);
+            }
+            Event::GuildDelete(guild_delete) if !guild_delete.unavailable => {
+                tracked.guilds.remove(&guild_delete.id);
+            }
+            Event::Ready(ready) => {
+                tracked.guilds = ready.guilds.iter().map(|guild| guild.id).collect();
+            }
+            _ => {}
+        }
+
+        drop(shards);
+    }
+
+    /// Update the tracked status of the given shard
+    pub fn update(&self, shard: &Shard) {
+        let now = Instant::now();
+        let connection = shard.status().clone();
+        let latency = shard.latency().average();
+        let session_id = shard.session().map(|session| session.id().to_owned());
+
+        let mut shards = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let tracked = shards.entry(shard.id()).or_default();
+
+        tracked.set_connection(connection, now);
+
+        if session_id != tracked.session_id {
+            tracked.session_started = session_id.as_ref().map(|_| now);
+            tracked.session_id = session_id;
+        }
+
+        tracked.latency = latency;
+        drop(shards);
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TrackedShard {
+    connected_before: bool,
+    connection: ConnectionStatus,
+    disconnected_since: Option<Instant>,
+    guilds: HashSet<Id<GuildMarker>>,
+    latency: Option<Duration>,
+    reconnects: u32,
+    session_id: Option<String>,
+    session_started: Option<Instant>,
+}
+
+impl Default for TrackedShard {
+    fn default() -> Self {
+        Self {
+            connected_before: false,
+            connection: ConnectionStatus::Identifying,
+            disconnected_since: None,
+            guilds: HashSet::new(),
+            latency: None,
+            reconnects: 0,
+            session_id: None,
+            session_started: None,
+        }
+    }
+}
+
+impl TrackedShard {
+    /// Set the connection status, counting a reconnect when the shard is
+    /// connected again after it was disconnected, identifying or resuming
+    fn set_connection(&mut self, connection: ConnectionStatus, now: Instant) {
+        if connection == ConnectionStatus::Connected {
+            if self.disconnected_since.take().is_some() && self.connected_before {
+                self.reconnects = self.reconnects.saturating_add(1);
+            }
+            self.connected_before = true;
+        } else {
+            self.disconnected_since.get_or_insert(now);
+        }
+
+        self.connection = connection;
+    }
+
+    fn status(&self, id: ShardId, now: Instant) -> ShardStatus {
+        ShardStatus {
+            connection: self.connection.clone(),
+            disconnected_for: self
+                .disconnected_since
+                .map(|disconnected_since| now.saturating_duration_since(disconnected_since)),
+            guild_count: self.guilds.len(),
+            id,
+            latency: self.latency,
+            reconnects: self.reconnects,
+            session_age: self
+                .session_started
+                .map(|session_started| now.saturating_duration_since(session_started)),
+        }
+    }
+}
+
+impl Bot {
+    /// Spawn a task that checks the shards' status every `interval` and logs a
+    /// summary of the shards that have been disconnected for at least
+    /// `threshold` using [`Bot::log`]
+    ///
+    /// The status is read from [`Bot::shard_tracker`], so it must be updated
+    /// for the shards to be checked
+    ///
+    /// The task stops when [`Shards::shutdown`] is called
+    pub fn spawn_stuck_shard_logger(self: Arc<Self>, interval: Duration, threshold: Duration) {
+        let tasks = self.tasks.clone();
+
+        tasks.spawn_internal(async move {
+            loop {
+                let shutdown_res = timeout(interval, self.tasks.shutting_down()).await;
+                if shutdown_res.is_ok() {
+                    break;
+                }
+
+                if let Some(summary) = stuck_shards_summary(&self.shard_tracker.status(), threshold)
+                {
+                    let _log_res = self.log(&summary).await;
+                }
+            }
+        });
+    }
+}
+
+impl Shards {
+    /// Update [`Bot::shard_tracker`] with the current state of the shards and
+    /// return their status
+    #[must_use]
+    pub fn status(&self, bot: &Bot) -> Vec<ShardStatus> {
+        for shard in &self.0 {
+            bot.shard_tracker.update(shard);
+        }
+
+        bot.shard_tracker
+            .status()
+            .into_iter()
+            .filter(|status| self.0.iter().any(|shard| shard.id() == status.id))
+            .collect()
+    }
+}
+
+fn stuck_shards_summary(statuses: &[ShardStatus], threshold: Duration) -> Option<String> {
+    let stuck = statuses
+        .iter()
+        .filter(|status| status.is_stuck(threshold))
+        .collect::<Vec<_>>();
+
+    if stuck.is_empty() {
+        return None;
+    }
+
+    Some(stuck.iter().fold(
+        format!(
+            "{} of {} shards are stuck reconnecting:",
+            stuck.len(),
+            statuses.len()
+        ),
+        |mut summary, status| {
+            let _write_res = write!(
+                summary,
+                "\nshard {}: {} for {}s, {} reconnects",
+                status.id,
+                connection_description(&status.connection),
+                status.disconnected_for.unwrap_or_default().as_secs(),
+                status.reconnects
+            );
+            summary
+        },
+    ))
+}
+
+fn connection_description(connection: &ConnectionStatus) -> String {
+    match connection {
+        ConnectionStatus::Connected => "connected".to_owned(),
+        ConnectionStatus::Disconnected {
+            close_code,
+            reconnect_attempts,
+        } => format!(
+            "disconnected with close code {}, {reconnect_attempts} attempts",
+            close_code.map_or_else(|| "none".to_owned(), |code| code.to_string())
+        ),
+        ConnectionStatus::FatallyClosed { close_code } => {
+            format!("fatally closed: {close_code}")
+        }
+        ConnectionStatus::Identifying => "identifying".to_owned(),
+        ConnectionStatus::Resuming => "resuming".to_owned(),
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use twilight_gateway::{ConnectionStatus, ShardId};
+use twilight_model::id::Id;
+
+use crate::status::{ShardStatus, ShardTracker, TrackedShard, stuck_shards_summary};
+
+fn status(number: u64, disconnected_for: Option<Duration>) -> ShardStatus {
+    ShardStatus {
+        connection: if disconnected_for.is_some() {
+            ConnectionStatus::Disconnected {
+                close_code: Some(4000),
+                reconnect_attempts: 3,
+            }
+        } else {
+            ConnectionStatus::Connected
+        },
+        disconnected_for,
+        guild_count: 0,
+        id: ShardId::new(number, 3),
+        latency: None,
+        reconnects: 2,
+        session_age: None,
+    }
+}
+
+fn track_guilds(tracker: &ShardTracker, shard_id: ShardId, guild_ids: &[u64]) {
+    tracker.0.lock().unwrap().insert(
+        shard_id,
+        TrackedShard {
+            guilds: guild_ids
+                .iter()
+                .map(|guild_id| Id::new(*guild_id))
+                .collect(),
+            ..TrackedShard::default()
+        },
+    );
+}
+
+#[test]
+fn shard_status_stuck() {
+    let threshold = Duration::from_mins(1);
+
+    assert!(!status(0, None).is_stuck(threshold));
+    assert!(!status(0, Some(Duration::from_secs(30))).is_stuck(threshold));
+    assert!(status(0, Some(threshold)).is_stuck(threshold));
+}
+
+#[test]
+fn shard_tracker_replace() {
+    let tracker = ShardTracker::default();
+    track_guilds(&tracker, ShardId::new(0, 2), &[1, 2]);
+    track_guilds(&tracker, ShardId::new(1, 2), &[3]);
+
+    let new_tracker = ShardTracker::default();
+    track_guilds(&new_tracker, ShardId::new(0, 3), &[1]);
+    track_guilds(&new_tracker, ShardId::new(1, 3), &[2]);
+    track_guilds(&new_tracker, ShardId::new(2, 3), &[3]);
+
+    tracker.replace([ShardId::new(0, 2), ShardId::new(1, 2)], &new_tracker);
+
+    assert_eq!(tracker.guild_count(), 3);
+    assert_eq!(
+        tracker
+            .status()
+            .iter()
+            .map(|status| status.id)
+            .collect::<HashSet<_>>(),
+        HashSet::from([ShardId::new(0, 3), ShardId::new(1, 3), ShardId::new(2, 3)])
+    );
+    assert_eq!(new_tracker.guild_count(), 0);
+}
+
+#[test]
+fn stuck_shards_summary_lists_stuck() {
+    let threshold = Duration::from_mins(1);
+    let statuses = [
+        status(0, None),
+        status(1, Some(Duration::from_secs(30))),
+        status(2, Some(Duration::from_secs(90))),
+    ];
+
+    assert_eq!(stuck_shards_summary(&statuses[..2], threshold), None);
+    assert_eq!(
+        stuck_shards_summary(&statuses, threshold).unwrap(),
+        "1 of 3 shards are stuck reconnecting:\nshard [2, 3]: disconnected with close code 4000, \
+         3 attempts for 90s, 2 reconnects"
+    );
+}
+
+#[test]
+fn tracked_shard_resuming_is_disconnected() {
+    let start = Instant::now();
+    let mut tracked = TrackedShard::default();
+
+    tracked.set_connection(ConnectionStatus::Connected, start);
+    assert_eq!(tracked.disconnected_since, None);
+
+    tracked.set_connection(ConnectionStatus::Resuming, start);
+    tracked.set_connection(ConnectionStatus::Resuming, start + Duration::from_secs(5));
+    assert_eq!(tracked.disconnected_since, Some(start));
+    assert_eq!(tracked.reconnects, 0);
+
+    tracked.set_connection(ConnectionStatus::Connected, start + Duration::from_secs(10));
+    assert_eq!(tracked.disconnected_since, None);
+    assert_eq!(tracked.reconnects, 1);
+}
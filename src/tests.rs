@@ -0,0 +1,65 @@
+use tokio::runtime::Builder;
+use twilight_gateway::{Intents, Shard, ShardId};
+use twilight_model::{
+    gateway::{event::Event, payload::incoming::GuildCreate},
+    id::{Id, marker::GuildMarker},
+};
+
+use crate::builder::{BotBuilder, ShardSelection};
+
+const TOKEN: &str = "MTIzNDU2Nzg5MDEyMzQ1Njc4.GabcDe.secret";
+
+fn guild_create(guild_id: Id<GuildMarker>) -> Event {
+    Event::GuildCreate(Box::new(GuildCreate(
+        serde_json::from_value(serde_json::json!({
+            "afk_timeout": 300,
+            "channels": [],
+            "default_message_notifications": 0,
+            "explicit_content_filter": 0,
+            "features": [],
+            "id": guild_id.to_string(),
+            "member_count": 1,
+            "members": [],
+            "mfa_level": 0,
+            "name": "guild",
+            "nsfw_level": 0,
+            "owner_id": "2",
+            "preferred_locale": "en-US",
+            "premium_progress_bar_enabled": false,
+            "premium_tier": 0,
+            "presences": [],
+            "roles": [],
+            "stage_instances": [],
+            "stickers": [],
+            "system_channel_flags": 0,
+            "threads": [],
+            "verification_level": 0,
+            "voice_states": []
+        }))
+        .unwrap(),
+    )))
+}
+
+#[test]
+fn bot_update() {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let (bot, _) = BotBuilder::new(TOKEN, Intents::GUILDS)
+                .fetch_application(false)
+                .fetch_user(false)
+                .shards(ShardSelection::Total(1))
+                .build()
+                .await
+                .unwrap();
+            let shard = Shard::new(ShardId::ONE, TOKEN.to_owned(), Intents::GUILDS);
+
+            bot.update(&shard, &guild_create(Id::new(1)));
+
+            assert_eq!(bot.shard_tracker.guild_count(), 1);
+            #[cfg(feature = "cache")]
+            assert_eq!(bot.cache.guild(Id::new(1)).unwrap().name(), "guild");
+        });
+}